@@ -37,6 +37,17 @@ export type KeyStrokeDisplayInfo = {
   readonly progress: number,
   readonly lapEndPositions: number[],
   readonly lapEndTime: number[],
+  readonly liveMetrics: LiveMetrics,
+}
+
+export type LiveMetrics = {
+  readonly elapsedTimeMs: number,
+  readonly kpm: number,
+  readonly rollingKpm: number,
+  readonly accuracy: number,
+  readonly missedCount: number,
+  // null before anything is typed because speed is unknown
+  readonly estimatedRemainingTimeMs: number | null,
 }
 
 export type DisplayInfo = {
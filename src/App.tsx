@@ -19,7 +19,8 @@ export function App() {
     gameState,
     library, libraryOperator,
     keyStrokeCountThreshold, setKeyStrokeCountThreshold,
-    prepareStartGame, startGame, onInput, cancelGame, backToModeSelect,
+    timeLimitSec, setTimeLimitSec,
+    prepareStartGame, prepareRetryMissedVocabularies, startGame, onInput, onElapse, cancelGame, backToModeSelect,
     displayInfo, mayFinishPromise
   ] = useGameControl(registerNotification);
 
@@ -37,6 +38,8 @@ export function App() {
                 libraryOperator={libraryOperator}
                 keyStrokeCountThreshold={keyStrokeCountThreshold}
                 setKeyStrokeCountThreshold={setKeyStrokeCountThreshold}
+                timeLimitSec={timeLimitSec}
+                setTimeLimitSec={setTimeLimitSec}
                 prepareStartGame={prepareStartGame}
               />
               : gameState === 'TransitionToTyping' ?
@@ -48,10 +51,11 @@ export function App() {
                   <TypingView
                     displayInfo={displayInfo}
                     onInput={onInput}
+                    onElapse={onElapse}
                     cancelGame={cancelGame}
                     mayFinishPromise={mayFinishPromise}
                   />
-                  : <ResultView backToModeSelect={backToModeSelect} retryGame={prepareStartGame} />
+                  : <ResultView backToModeSelect={backToModeSelect} retryGame={prepareStartGame} retryMissedVocabularies={prepareRetryMissedVocabularies} />
           }
         </NotificationContext.Provider>
       </ThemeProvider >
@@ -1,15 +1,22 @@
-import { useState } from "react";
+import { useEffect, useState } from "react";
+import { load_statistics } from "pkg/typer_concierge_web";
 import { useLibrary } from "./useLibrary";
 import { useTypingEngine } from "./useTypingEngine";
 import { DisplayInfo, GameState, Library, LibraryOperator } from "@/@types/type";
 import { NotificationRegistererMap } from "./useNotification";
 import { trackEvent } from "@/util/analyticsUtils";
+import { errorMessage } from '@/util/errorUtils';
 
 export type KeyStrokeCountThreshold = number;
 export type KeyStrokeCountThresholdSetter = (value: KeyStrokeCountThreshold) => void;
+// null means the game is not time limited
+export type TimeLimitSec = number | null;
+export type TimeLimitSecSetter = (value: TimeLimitSec) => void;
 export type PrepareStartGame = () => void;
+export type PrepareRetryMissedVocabularies = () => void;
 export type StartGame = () => void;
 export type OnInput = (key: string, elapsedTime: number) => void;
+export type OnElapse = (elapsedTime: number) => void;
 export type CancelGame = () => void;
 export type BackToModeSelect = () => void;
 export type MayFinishPromise = Promise<void>;
@@ -21,9 +28,13 @@ export function useGameControl(notificationRegisterer: NotificationRegistererMap
     LibraryOperator,
     KeyStrokeCountThreshold,
     KeyStrokeCountThresholdSetter,
+    TimeLimitSec,
+    TimeLimitSecSetter,
     PrepareStartGame,
+    PrepareRetryMissedVocabularies,
     StartGame,
     OnInput,
+    OnElapse,
     CancelGame,
     BackToModeSelect,
     DisplayInfo,
@@ -31,26 +42,36 @@ export function useGameControl(notificationRegisterer: NotificationRegistererMap
   ] {
   const [gameState, setGameState] = useState<GameState>('ModeSelect');
   const [keyStrokeCountThreshold, setKeyStrokeCountThreshold] = useState<KeyStrokeCountThreshold>(150);
+  const [timeLimitSec, setTimeLimitSec] = useState<TimeLimitSec>(null);
 
   let resolveFn: () => void;
   const mayFinishPromise = new Promise<void>((resolve, _) => {
     resolveFn = resolve;
   });
 
-  const [displayInfo, onConfirmQuery, onStartGame, onInput] = useTypingEngine(() => {
+  const [displayInfo, onConfirmQuery, onConfirmMissedVocabularies, onStartGame, onInput, onAbortGame, onElapse] = useTypingEngine(() => {
     resolveFn();
     setGameState('Finished');
   });
   const [library, libraryOperator] = useLibrary(notificationRegisterer);
 
+  // Statistics persisted in previous visits are restored, and saved automatically after that
+  useEffect(() => {
+    try {
+      load_statistics();
+    } catch (e) {
+      notificationRegisterer.get('warning')?.('統計データ読み込みエラー', errorMessage(e));
+    }
+  }, []);
+
   const prepareStartGame = () => {
     const usedDictionaryType = library.usedDictionaryType;
     const usedDictionaries = library.usedDictionaries;
 
     try {
-      onConfirmQuery(usedDictionaryType, usedDictionaries, keyStrokeCountThreshold);
+      onConfirmQuery(usedDictionaryType, usedDictionaries, keyStrokeCountThreshold, timeLimitSec);
     } catch (e) {
-      notificationRegisterer.get('error')?.('問題文作成エラー', e instanceof Error ? e.message : String(e));
+      notificationRegisterer.get('error')?.('問題文作成エラー', errorMessage(e));
       return;
     }
     setGameState('TransitionToTyping');
@@ -59,14 +80,28 @@ export function useGameControl(notificationRegisterer: NotificationRegistererMap
       used_dictionary_type: usedDictionaryType,
       used_dictionaries: usedDictionaries,
       key_stroke_count_threshold: keyStrokeCountThreshold,
+      time_limit_sec: timeLimitSec,
     });
   }
 
+  // Vocabularies missed in the last game are typed again
+  const prepareRetryMissedVocabularies = () => {
+    try {
+      onConfirmMissedVocabularies();
+    } catch (e) {
+      notificationRegisterer.get('error')?.('問題文作成エラー', errorMessage(e));
+      return;
+    }
+    setGameState('TransitionToTyping');
+
+    trackEvent('retry_missed_vocabularies');
+  }
+
   const startGame = () => {
     try {
       onStartGame();
     } catch (e) {
-      notificationRegisterer.get('error')?.('ゲーム開始エラー', e instanceof Error ? e.message : String(e));
+      notificationRegisterer.get('error')?.('ゲーム開始エラー', errorMessage(e));
       setGameState('ModeSelect');
     }
     setGameState('Typing');
@@ -76,12 +111,24 @@ export function useGameControl(notificationRegisterer: NotificationRegistererMap
     try {
       onInput(key, elapsedTime);
     } catch (e) {
-      notificationRegisterer.get('error')?.('キー入力処理エラー', e instanceof Error ? e.message : String(e));
+      notificationRegisterer.get('error')?.('キー入力処理エラー', errorMessage(e));
+      onAbortGame();
+      setGameState('ModeSelect');
+    }
+  }
+
+  const onElapseWrapper = (elapsedTime: number) => {
+    try {
+      onElapse(elapsedTime);
+    } catch (e) {
+      notificationRegisterer.get('error')?.('制限時間処理エラー', errorMessage(e));
+      onAbortGame();
       setGameState('ModeSelect');
     }
   }
 
   const cancelGame = () => {
+    onAbortGame();
     setGameState('ModeSelect');
     trackEvent('cancel_game');
   }
@@ -91,5 +138,5 @@ export function useGameControl(notificationRegisterer: NotificationRegistererMap
     trackEvent('back_to_mode_select');
   }
 
-  return [gameState, library, libraryOperator, keyStrokeCountThreshold, setKeyStrokeCountThreshold, prepareStartGame, startGame, onInputWrapper, cancelGame, backToModeSelect, displayInfo, mayFinishPromise]
+  return [gameState, library, libraryOperator, keyStrokeCountThreshold, setKeyStrokeCountThreshold, timeLimitSec, setTimeLimitSec, prepareStartGame, prepareRetryMissedVocabularies, startGame, onInputWrapper, onElapseWrapper, cancelGame, backToModeSelect, displayInfo, mayFinishPromise]
 }
@@ -2,6 +2,7 @@ import { useEffect, useReducer, useState, useActionState, startTransition } from
 import { Library, LibraryOperator } from '@/@types/type';
 import { get_dictionary_catalog, DictionaryType, DictionaryOrigin, DictionaryCatalog, confirm_query, QueryRequestFromUI } from 'pkg/typer_concierge_web';
 import { NotificationRegistererMap } from './useNotification';
+import { errorMessage } from '@/util/errorUtils';
 
 export function useLibrary(notificationRegisterer: NotificationRegistererMap): [Library, LibraryOperator] {
 
@@ -16,6 +17,8 @@ export function useLibrary(notificationRegisterer: NotificationRegistererMap): [
   const initialCatalog: DictionaryCatalog = {
     word: [],
     sentence: [],
+    failed: [],
+    userDictionaryRestoreError: null,
   }
   const loadCatalog = async (_: { catalog: DictionaryCatalog, error: any }) => {
     const loadTimestamp = Date.now();
@@ -24,7 +27,7 @@ export function useLibrary(notificationRegisterer: NotificationRegistererMap): [
     try {
       catalog = await get_dictionary_catalog();
     } catch (e: any) {
-      return { catalog: initialCatalog, error: e instanceof Error ? e.message : String(e), t: loadTimestamp };
+      return { catalog: initialCatalog, error: errorMessage(e), t: loadTimestamp };
     }
 
     // XXX
@@ -45,6 +48,14 @@ export function useLibrary(notificationRegisterer: NotificationRegistererMap): [
     if (catalog.error) {
       notificationRegisterer.get('error')?.('辞書情報取得エラー', catalog.error);
     }
+
+    catalog.catalog.failed.forEach(failure => {
+      notificationRegisterer.get('warning')?.('辞書読み込みエラー', failure.name);
+    });
+
+    if (catalog.catalog.userDictionaryRestoreError) {
+      notificationRegisterer.get('warning')?.('ユーザー定義辞書復元エラー', catalog.catalog.userDictionaryRestoreError);
+    }
   }, [catalog]);
 
   // 
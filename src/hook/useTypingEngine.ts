@@ -1,13 +1,16 @@
-import _, { useState } from 'react';
+import _, { useRef, useState } from 'react';
 import { DisplayInfo } from '@/@types/type';
-import { DictionaryOrigin, DictionaryType, start_game, stroke_key, QueryRequestFromUI, confirm_query } from 'pkg/typer_concierge_web';
+import { DictionaryOrigin, DictionaryType, start_game, stroke_key, QueryRequestFromUI, confirm_query, confirm_missed_vocabularies, abort_game, check_time_limit, StrokeKeyResult, WasmErrorObject } from 'pkg/typer_concierge_web';
 
 export type FinishedHandler = () => void;
-export type OnConfirmQuery = (usedDictionaryType: DictionaryType, usedDictionaries: [DictionaryOrigin, string][], keyStrokeCountThreshold: number) => void;
+export type OnConfirmQuery = (usedDictionaryType: DictionaryType, usedDictionaries: [DictionaryOrigin, string][], keyStrokeCountThreshold: number, timeLimitSec: number | null) => void;
+export type OnConfirmMissedVocabularies = () => void;
 export type OnStartGame = () => void;
 export type OnInput = (c: string, elapsedTimeMs: number) => void;
+export type OnAbortGame = () => void;
+export type OnElapse = (elapsedTimeMs: number) => void;
 
-export function useTypingEngine(finishedHandler: FinishedHandler): [DisplayInfo, OnConfirmQuery, OnStartGame, OnInput] {
+export function useTypingEngine(finishedHandler: FinishedHandler): [DisplayInfo, OnConfirmQuery, OnConfirmMissedVocabularies, OnStartGame, OnInput, OnAbortGame, OnElapse] {
   const [displayInfo, setDisplayInfo] = useState<DisplayInfo>({
     view: {
       view: '',
@@ -22,29 +25,97 @@ export function useTypingEngine(finishedHandler: FinishedHandler): [DisplayInfo,
       progress: 0,
       lapEndPositions: [],
       lapEndTime: [],
+      liveMetrics: {
+        elapsedTimeMs: 0,
+        kpm: 0,
+        rollingKpm: 0,
+        accuracy: 0,
+        missedCount: 0,
+        estimatedRemainingTimeMs: null,
+      },
     }
   });
+  // Time limit of the confirmed query. Games without time limit need no check on timer ticks
+  const confirmedTimeLimitSec = useRef<number | null>(null);
 
   const onStartGame = () => {
-    // XXX try-catch
-    const displayInfo = start_game();
-    setDisplayInfo(displayInfo);
+    try {
+      const displayInfo = start_game();
+      setDisplayInfo(displayInfo);
+    } catch (e: any) {
+      const error = e as WasmErrorObject;
+      // Game cannot be started without confirmed query, so this is a bug of the caller
+      console.error(`${error.code}: ${error.message}`);
+      throw e;
+    }
   }
 
   const onInput = (c: string, elapsedTimeMs: number) => {
-    // XXX try-catch
-    const result = stroke_key({ key: c, elapsedTimeMs: elapsedTimeMs })
+    let result: StrokeKeyResult;
+    try {
+      result = stroke_key({ key: c, elapsedTimeMs: elapsedTimeMs })
+    } catch (e: any) {
+      const error = e as WasmErrorObject;
+      switch (error.code) {
+        // Keys which cannot be typed (e.g. function keys) are just ignored
+        case 'invalid_key_stroke':
+          return;
+        // Key strokes after finishing can arrive before the view is switched
+        case 'illegal_game_transition':
+          return;
+        default:
+          throw e;
+      }
+    }
+
     setDisplayInfo(result.displayInformation);
     if (result.isFinished) {
       finishedHandler();
     }
   }
 
-  const onConfirmQuery = (usedDictionaryType: DictionaryType, usedDictionaries: [DictionaryOrigin, string][], keyStrokeCountThreshold: number) => {
+  // Time limited game is finished even when no key is stroked
+  const onElapse = (elapsedTimeMs: number) => {
+    if (confirmedTimeLimitSec.current === null) {
+      return;
+    }
+
+    let isFinished: boolean;
+    try {
+      isFinished = check_time_limit(BigInt(elapsedTimeMs));
+    } catch (e: any) {
+      const error = e as WasmErrorObject;
+      // Timer can tick after the game is finished
+      if (error.code === 'illegal_game_transition') {
+        return;
+      }
+      throw e;
+    }
+
+    if (isFinished) {
+      finishedHandler();
+    }
+  };
+
+  // Cancelled game is not merged into aggregated result
+  const onAbortGame = () => {
+    try {
+      abort_game(false);
+    } catch (e: any) {
+      const error = e as WasmErrorObject;
+      // Game may be already finished or not started yet
+      if (error.code !== 'illegal_game_transition') {
+        throw e;
+      }
+    }
+  };
+
+  const onConfirmQuery = (usedDictionaryType: DictionaryType, usedDictionaries: [DictionaryOrigin, string][], keyStrokeCountThreshold: number, timeLimitSec: number | null) => {
     let request: QueryRequestFromUI = {
       dictionaryType: usedDictionaryType,
       usedDictionaries: usedDictionaries,
       keyStrokeCountThreshold: null,
+      timeLimitSec: timeLimitSec,
     }
 
     if (usedDictionaryType == 'word') {
@@ -52,7 +123,14 @@ export function useTypingEngine(finishedHandler: FinishedHandler): [DisplayInfo,
     }
 
     confirm_query(request);
+    confirmedTimeLimitSec.current = timeLimitSec;
+  };
+
+  // Missed vocabularies are retried without time limit
+  const onConfirmMissedVocabularies = () => {
+    confirm_missed_vocabularies();
+    confirmedTimeLimitSec.current = null;
   };
 
-  return [displayInfo, onConfirmQuery, onStartGame, onInput]
+  return [displayInfo, onConfirmQuery, onConfirmMissedVocabularies, onStartGame, onInput, onAbortGame, onElapse]
 }
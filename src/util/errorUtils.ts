@@ -0,0 +1,15 @@
+import { WasmErrorObject } from 'pkg/typer_concierge_web';
+
+/**
+ * 例外から表示用のメッセージを取り出します
+ * wasmからは Error ではなく WasmErrorObject が投げられるため、message を持つオブジェクトはそれを使います
+ * @param e - 捕捉した例外
+ */
+export function errorMessage(e: unknown): string {
+  if (e instanceof Error) {
+    return e.message;
+  }
+
+  const message = (e as Partial<WasmErrorObject> | null)?.message;
+  return typeof message === 'string' ? message : String(e);
+}
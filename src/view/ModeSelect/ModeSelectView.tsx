@@ -1,14 +1,26 @@
-import _, { useEffect } from 'react';
+import _, { useContext, useEffect, useState } from 'react';
 import { SelectDictionaryPane } from './SelectDictionaryPane';
 import { Box, Button, ButtonGroup, CircularProgress, Grid, IconButton, Input, Slider, Stack, Step, StepLabel, Stepper, Tooltip, Typography } from '@mui/material';
 import { Refresh } from '@mui/icons-material';
-import { DictionaryType } from 'pkg/typer_concierge_web';
+import { DictionaryType, get_lap_policy, LapPolicy, set_lap_policy } from 'pkg/typer_concierge_web';
 import { trackPageView } from '@/util/analyticsUtils';
 import { FixedFullScreenLayout } from '@/layout/FixedFullScreen';
-import { KeyStrokeCountThreshold, KeyStrokeCountThresholdSetter, PrepareStartGame } from '@/hook/useGameControl';
+import { KeyStrokeCountThreshold, KeyStrokeCountThresholdSetter, PrepareStartGame, TimeLimitSec, TimeLimitSecSetter } from '@/hook/useGameControl';
 import { Library, LibraryOperator } from "@/@types/type";
+import { errorMessage } from '@/util/errorUtils';
+import { NotificationContext } from '@/App';
 
 const LAP_LENGTH = 50;
+const TIME_LIMIT_OPTIONS: TimeLimitSec[] = [null, 60, 120, 300];
+const LAP_POLICY_OPTIONS: [LapPolicy, string][] = [
+  [{ type: 'ideal_key_stroke', count: LAP_LENGTH }, `${LAP_LENGTH}打鍵ごと`],
+  [{ type: 'key_stroke', count: LAP_LENGTH }, `実打鍵${LAP_LENGTH}回ごと`],
+  [{ type: 'vocabulary' }, '1語ごと'],
+];
+
+function isSameLapPolicy(a: LapPolicy, b: LapPolicy): boolean {
+  return JSON.stringify(a) === JSON.stringify(b);
+}
 
 function ModeSelectInstruction(props: { selectedDictionaryType: DictionaryType, usedDictionariesSelected: boolean }) {
   return (
@@ -34,10 +46,24 @@ function ModeSelectInstruction(props: { selectedDictionaryType: DictionaryType,
   );
 }
 
-export function ModeSelectView(props: { library: Library, libraryOperator: LibraryOperator, keyStrokeCountThreshold: KeyStrokeCountThreshold, setKeyStrokeCountThreshold: KeyStrokeCountThresholdSetter, prepareStartGame: PrepareStartGame }) {
+export function ModeSelectView(props: { library: Library, libraryOperator: LibraryOperator, keyStrokeCountThreshold: KeyStrokeCountThreshold, setKeyStrokeCountThreshold: KeyStrokeCountThresholdSetter, timeLimitSec: TimeLimitSec, setTimeLimitSec: TimeLimitSecSetter, prepareStartGame: PrepareStartGame }) {
   // NOTE: 分割代入を使っていこう cf. <https://developer.mozilla.org/ja/docs/Web/JavaScript/Reference/Operators/Destructuring_assignment>
   const { library: { usedDictionaryType, usedDictionaries, catalog: availableDictionaries, isAvailableDictionariesLoading }, libraryOperator } = props;
 
+  const notificationRegisterer = useContext(NotificationContext);
+
+  // Lap policy is kept in the session, so it is only mirrored here for rendering
+  const [lapPolicy, setLapPolicy] = useState<LapPolicy>(get_lap_policy);
+  const changeLapPolicy = (lapPolicy: LapPolicy) => {
+    try {
+      set_lap_policy(lapPolicy);
+    } catch (e) {
+      notificationRegisterer.get('error')?.('ラップ設定エラー', errorMessage(e));
+      return;
+    }
+    setLapPolicy(lapPolicy);
+  }
+
   const keyStrokeCountThreshold = props.keyStrokeCountThreshold;
   const setKeyStrokeCountThreshold = props.setKeyStrokeCountThreshold;
 
@@ -133,8 +159,28 @@ export function ModeSelectView(props: { library: Library, libraryOperator: Libra
                   }
                 </Box>
 
+                <Grid container justifyContent={'center'}>
+                  <ButtonGroup size='small'>
+                    {TIME_LIMIT_OPTIONS.map(timeLimitSec => (
+                      <Button key={timeLimitSec ?? 0} variant={props.timeLimitSec === timeLimitSec ? 'contained' : 'outlined'} onClick={() => props.setTimeLimitSec(timeLimitSec)}>
+                        {timeLimitSec === null ? '時間制限なし' : `${timeLimitSec}秒`}
+                      </Button>
+                    ))}
+                  </ButtonGroup>
+                </Grid>
+
+                <Grid container justifyContent={'center'}>
+                  <ButtonGroup size='small'>
+                    {LAP_POLICY_OPTIONS.map(([option, label]) => (
+                      <Button key={label} variant={isSameLapPolicy(lapPolicy, option) ? 'contained' : 'outlined'} onClick={() => changeLapPolicy(option)}>
+                        ラップ: {label}
+                      </Button>
+                    ))}
+                  </ButtonGroup>
+                </Grid>
+
                 {
-                  usedDictionaryType == 'word'
+                  usedDictionaryType == 'word' && props.timeLimitSec === null
                     ? (
                       <Grid container justifyContent={'center'} >
                         <Grid container justifyContent={'center'} width={'75%'} >
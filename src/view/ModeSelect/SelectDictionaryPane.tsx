@@ -33,12 +33,6 @@ export function SelectDictionaryPane(props: { availableDictionaryList: Dictionar
     // XXX 文と単語の辞書の名前に被りがあった時に衝突しないか
     const used = usedDictionaryOneHot.has(`${dictionaryInfo.origin} ${dictionaryInfo.name}`);
 
-    // 辞書に無効な語彙を含むときの警告文の生成
-    let containErrorTooltipText = DICTIONARY_CONTAIN_ERROR_TOOLTIP_TEXT_BASE;
-    dictionaryInfo.invalidLineNumbers.forEach(lineNum => {
-      containErrorTooltipText = containErrorTooltipText.concat(`\r\n${lineNum}行目`);
-    });
-
     const onChange = (name: string, origin: DictionaryOrigin) => {
       if (used) {
         return () => {
@@ -54,15 +48,15 @@ export function SelectDictionaryPane(props: { availableDictionaryList: Dictionar
     const checkbox = (
       <ListItem key={i} disablePadding secondaryAction={
         <span className='ms-auto'>
-          {dictionaryInfo.invalidLineNumbers.length != 0 ?
+          {dictionaryInfo.diagnostics.length != 0 ?
             <Tooltip title={
               <React.Fragment>
                 <Typography variant='caption'>{DICTIONARY_CONTAIN_ERROR_TOOLTIP_TEXT_BASE}</Typography>
                 <ul>
                   {
-                    dictionaryInfo.invalidLineNumbers.map((lineNum, i) => {
+                    dictionaryInfo.diagnostics.map((diagnostic, i) => {
                       return <li key={i}>
-                        <Typography variant='caption'>{lineNum}行目</Typography>
+                        <Typography variant='caption'>{diagnostic.lineNumber}行目: {diagnostic.message}</Typography>
                       </li>
                     })
                   }
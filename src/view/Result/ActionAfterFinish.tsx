@@ -2,12 +2,16 @@ import React from 'react';
 import { Grid, Typography, IconButton, Stack } from '@mui/material';
 import ArrowBackIcon from '@mui/icons-material/ArrowBack';
 import ReplayIcon from '@mui/icons-material/Replay';
+import RestartAltIcon from '@mui/icons-material/RestartAlt';
 import { TileCard } from './TileCard';
 
 export const ActionAfterFinishPane: React.FC<{
   backToModeSelect: () => void;
   retry: () => void;
-}> = ({ backToModeSelect, retry }) => {
+  retryMissed: () => void;
+  // Retrying is available only when the last game has missed vocabularies
+  canRetryMissed: boolean;
+}> = ({ backToModeSelect, retry, retryMissed, canRetryMissed }) => {
   return (
     <TileCard>
       <Grid container justifyContent="space-around" alignItems="center">
@@ -26,6 +30,13 @@ export const ActionAfterFinishPane: React.FC<{
           <Typography variant="body1">リトライする</Typography>
           <Typography variant="caption">Enter</Typography>
         </Stack>
+
+        <Stack spacing={1} alignItems="center">
+          <IconButton size="large" aria-label="ミスした単語を再挑戦する" onClick={retryMissed} color='secondary' disabled={!canRetryMissed} >
+            <RestartAltIcon fontSize='large' />
+          </IconButton>
+          <Typography variant="body1">ミスを再挑戦</Typography>
+        </Stack>
       </Grid>
     </TileCard>
   );
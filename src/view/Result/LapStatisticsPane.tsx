@@ -0,0 +1,50 @@
+import React from 'react';
+import { LapStatistics } from 'pkg/typer_concierge_web';
+import { Table, TableBody, TableCell, TableContainer, TableHead, TableRow, Typography } from '@mui/material';
+import { TileCard } from './TileCard';
+
+export function LapStatisticsPane(props: { laps: LapStatistics }): React.JSX.Element {
+  const { laps, kpmStandardDeviation, fastestLapIndex, slowestLapIndex } = props.laps;
+
+  const lapColor = (index: number) => {
+    // Incomplete last lap is not compared, so coloring is meaningful only with multiple complete laps
+    if (laps.filter(lap => lap.isComplete).length <= 1) {
+      return undefined;
+    }
+
+    return index === fastestLapIndex ? 'success.main' : index === slowestLapIndex ? 'error.main' : undefined;
+  }
+
+  return (
+    <TileCard>
+      <Typography variant='h6'>ラップ</Typography>
+      <Typography variant='caption'>KPMの標準偏差: {kpmStandardDeviation.toFixed(1)}</Typography>
+      <TableContainer sx={{ maxHeight: 300 }}>
+        <Table size='small' stickyHeader>
+          <TableHead>
+            <TableRow>
+              <TableCell>#</TableCell>
+              <TableCell align='right'>時間</TableCell>
+              <TableCell align='right'>タイプ数</TableCell>
+              <TableCell align='right'>ミス</TableCell>
+              <TableCell align='right'>KPM</TableCell>
+              <TableCell align='right'>正確率</TableCell>
+            </TableRow>
+          </TableHead>
+          <TableBody>
+            {laps.map((lap, index) => (
+              <TableRow key={index}>
+                <TableCell sx={{ color: lapColor(index) }}>{index + 1}{lap.isComplete ? '' : '（途中）'}</TableCell>
+                <TableCell align='right'>{(lap.durationMs / 1000).toFixed(1)}秒</TableCell>
+                <TableCell align='right'>{lap.keyStrokeCount}</TableCell>
+                <TableCell align='right'>{lap.missedCount}</TableCell>
+                <TableCell align='right' sx={{ color: lapColor(index) }}>{Math.floor(lap.kpm)}</TableCell>
+                <TableCell align='right'>{Math.floor(lap.accuracy * 100)}%</TableCell>
+              </TableRow>
+            ))}
+          </TableBody>
+        </Table>
+      </TableContainer>
+    </TileCard>
+  );
+}
@@ -2,19 +2,21 @@ import React, { useEffect, useContext, useState } from 'react';
 import { NotificationContext } from '@/App';
 import { ResultSummaryPane } from './ResultSummaryPane';
 import { GameResult, get_result } from 'pkg/typer_concierge_web';
-import { Grid, Skeleton, Stack, Tooltip } from '@mui/material';
+import { Grid, Stack } from '@mui/material';
 import { trackPageView } from '@/util/analyticsUtils';
 import { ScrollableLayout } from '@/layout/Scrollable';
 import { ActionAfterFinishPane } from './ActionAfterFinish';
 import { ShareResultPane } from './ShareResult';
 import { SingleKeyStrokeSkillPane } from './SingleKeyStrokeSkillPane';
-import { BackToModeSelect, PrepareStartGame } from '@/hook/useGameControl';
+import { BackToModeSelect, PrepareRetryMissedVocabularies, PrepareStartGame } from '@/hook/useGameControl';
 import { SingleKeyStrokePlot as SingleKeyStrokeScatterPane } from './SingleKeyStrokeScatterPane';
 import { StatisticsDataControlPane } from './StatisticsDataControlPane';
+import { LapStatisticsPane } from './LapStatisticsPane';
 import { calculateAccuracy, calculateWPS } from './utility';
+import { errorMessage } from '@/util/errorUtils';
 
 // | undefinedとしているのは初回には結果はないため
-export function ResultView(props: { backToModeSelect: BackToModeSelect, retryGame: PrepareStartGame }): React.JSX.Element {
+export function ResultView(props: { backToModeSelect: BackToModeSelect, retryGame: PrepareStartGame, retryMissedVocabularies: PrepareRetryMissedVocabularies }): React.JSX.Element {
   const notificationRegisterer = useContext(NotificationContext);
 
   const initialResultStatistics: GameResult = {
@@ -45,7 +47,16 @@ export function ResultView(props: { backToModeSelect: BackToModeSelect, retryGam
       },
       totalTimeMs: 0,
       singleKeyStrokeSkills: [],
-    }
+    },
+    isAborted: false,
+    laps: {
+      laps: [],
+      kpmStandardDeviation: 0,
+      fastestLapIndex: null,
+      slowestLapIndex: null,
+    },
+    statisticsSaveError: null,
+    missedVocabularyCount: 0,
   };
   const [resultStatistics, setResultStatistics] = useState<GameResult>(initialResultStatistics);
 
@@ -76,8 +87,13 @@ export function ResultView(props: { backToModeSelect: BackToModeSelect, retryGam
       typingResult.singleKeyStrokeSkills = result.aggregatedResult.singleKeyStrokeSkills;
 
       setResultStatistics(result);
+
+      // Result is still shown because the game itself is finished
+      if (result.statisticsSaveError) {
+        notificationRegisterer.get('warning')?.('統計データ保存エラー', result.statisticsSaveError);
+      }
     } catch (e) {
-      notificationRegisterer.get('error')?.('結果生成エラー', e instanceof Error ? e.message : String(e));
+      notificationRegisterer.get('error')?.('結果生成エラー', errorMessage(e));
     }
 
   }, []);
@@ -93,7 +109,7 @@ export function ResultView(props: { backToModeSelect: BackToModeSelect, retryGam
       <Grid container width={'100%'} spacing={2} padding={2} >
         <Grid size={3} >
           <Stack spacing={2} justifyContent={'space-between'} height={'100%'} >
-            <ActionAfterFinishPane backToModeSelect={props.backToModeSelect} retry={props.retryGame} />
+            <ActionAfterFinishPane backToModeSelect={props.backToModeSelect} retry={props.retryGame} retryMissed={props.retryMissedVocabularies} canRetryMissed={resultStatistics.missedVocabularyCount > 0} />
             <ShareResultPane summary={resultStatistics.thisResult} />
           </Stack>
         </Grid>
@@ -101,9 +117,7 @@ export function ResultView(props: { backToModeSelect: BackToModeSelect, retryGam
           <ResultSummaryPane summary={resultStatistics.thisResult} />
         </Grid>
         <Grid size={6} >
-          <Tooltip title='工事中...'>
-            <Skeleton variant='rectangular' width={'100%'} height={'100%'} />
-          </Tooltip>
+          <LapStatisticsPane laps={resultStatistics.laps} />
         </Grid>
         <Grid size={1} >
           <StatisticsDataControlPane onResetStatistics={
@@ -1,13 +1,15 @@
-import _, { useContext } from 'react';
+import _, { useContext, useState } from 'react';
 import { IconButton, Stack, Tooltip, useTheme } from '@mui/material';
 import { TileCard } from './TileCard';
 import { Analytics, Delete } from '@mui/icons-material';
 import Color from 'color';
 import { NotificationContext } from '@/App';
 import { reset_statistics } from 'pkg/typer_concierge_web';
+import { StatisticsHistoryDialog } from './StatisticsHistoryDialog';
 
 export function StatisticsDataControlPane(props: { onResetStatistics: () => void }) {
   const notificationRegisterer = useContext(NotificationContext);
+  const [isHistoryOpen, setIsHistoryOpen] = useState<boolean>(false);
 
   const resetStatistics = () => {
     try {
@@ -20,11 +22,11 @@ export function StatisticsDataControlPane(props: { onResetStatistics: () => void
   }
 
   const manageResult = () => {
-    notificationRegisterer.get('warning')?.("未実装エラー", "統計データの管理は未実装です");
+    setIsHistoryOpen(true);
   }
 
   const theme = useTheme();
-  const STATISTICS_FUNCTION_DESCRIPTION = `統計データはリロードやページを離れたりしない限り累積されていきます。何回も繰り返しタイピングを行っていくことでより正確な統計データを取得することができます。`;
+  const STATISTICS_FUNCTION_DESCRIPTION = `統計データはブラウザに保存され、リロードやページを離れても累積されていきます。何回も繰り返しタイピングを行っていくことでより正確な統計データを取得することができます。`;
 
   return (
     <TileCard sx={{ height: '100%', backgroundColor: `${Color(theme.palette.success.main).alpha(0.2).rgb().string()}` }}>
@@ -38,6 +40,7 @@ export function StatisticsDataControlPane(props: { onResetStatistics: () => void
           <Delete fontSize='large' />
         </IconButton>
       </Stack>
+      <StatisticsHistoryDialog open={isHistoryOpen} onClose={() => setIsHistoryOpen(false)} />
     </TileCard >
   );
 };
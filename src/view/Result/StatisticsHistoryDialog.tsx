@@ -0,0 +1,128 @@
+import _, { useContext, useEffect, useState } from 'react';
+import { Button, Dialog, DialogActions, DialogContent, DialogTitle, IconButton, Table, TableBody, TableCell, TableHead, TablePagination, TableRow } from '@mui/material';
+import { Delete } from '@mui/icons-material';
+import { NotificationContext } from '@/App';
+import { delete_history_entry, export_confusion_table_csv, export_history_csv, export_key_stroke_skills_csv, export_statistics_json, get_history_page, HistoryPage, import_statistics_json } from 'pkg/typer_concierge_web';
+import { calculateAccuracy, calculateWPM } from './utility';
+import { errorMessage } from '@/util/errorUtils';
+
+const ROWS_PER_PAGE = 10;
+
+function download(fileName: string, content: string, mimeType: string) {
+  const url = URL.createObjectURL(new Blob([content], { type: mimeType }));
+
+  const anchor = document.createElement('a');
+  anchor.href = url;
+  anchor.download = fileName;
+  anchor.click();
+
+  URL.revokeObjectURL(url);
+}
+
+export function StatisticsHistoryDialog(props: { open: boolean, onClose: () => void }) {
+  const notificationRegisterer = useContext(NotificationContext);
+
+  const [page, setPage] = useState<number>(0);
+  const [historyPage, setHistoryPage] = useState<HistoryPage>({ entries: [], totalCount: 0 });
+
+  const loadPage = (page: number) => {
+    setHistoryPage(get_history_page(page * ROWS_PER_PAGE, ROWS_PER_PAGE));
+    setPage(page);
+  }
+
+  useEffect(() => {
+    if (props.open) {
+      loadPage(0);
+    }
+  }, [props.open]);
+
+  const deleteEntry = (id: string) => {
+    try {
+      delete_history_entry(id);
+    } catch (e) {
+      notificationRegisterer.get('error')?.("エラー", `履歴の削除に失敗しました: ${errorMessage(e)}`);
+    }
+
+    // Go back to the previous page when the last entry in the page is deleted
+    const isLastEntryInPage = historyPage.entries.length == 1 && page > 0;
+    loadPage(isLastEntryInPage ? page - 1 : page);
+  }
+
+  const exportStatistics = (exporter: () => string, fileName: string, mimeType: string) => {
+    try {
+      download(fileName, exporter(), mimeType);
+    } catch (e) {
+      notificationRegisterer.get('error')?.("エラー", `統計データのエクスポートに失敗しました: ${errorMessage(e)}`);
+    }
+  }
+
+  const importStatistics = async (e: React.ChangeEvent<HTMLInputElement>) => {
+    const file = e.target.files?.[0];
+    // Allow selecting the same file again
+    e.target.value = '';
+    if (file === undefined) {
+      return;
+    }
+
+    try {
+      const summary = import_statistics_json(await file.text());
+      notificationRegisterer.get('success')?.("成功", `${summary.importedCount}件の履歴をインポートしました（重複${summary.duplicatedCount}件）`);
+    } catch (e) {
+      notificationRegisterer.get('error')?.("エラー", `統計データのインポートに失敗しました: ${errorMessage(e)}`);
+    }
+
+    loadPage(0);
+  }
+
+  return (
+    <Dialog open={props.open} onClose={props.onClose} maxWidth='md' fullWidth>
+      <DialogTitle>履歴</DialogTitle>
+      <DialogContent>
+        <Table size='small'>
+          <TableHead>
+            <TableRow>
+              <TableCell>日時</TableCell>
+              <TableCell>辞書</TableCell>
+              <TableCell align='right'>WPM</TableCell>
+              <TableCell align='right'>正確率</TableCell>
+              <TableCell />
+            </TableRow>
+          </TableHead>
+          <TableBody>
+            {historyPage.entries.map(entry => (
+              <TableRow key={entry.id}>
+                <TableCell>{new Date(entry.finishedAt).toLocaleString()}</TableCell>
+                <TableCell>{entry.usedDictionaries.map(([_, name]) => name).join(', ')}</TableCell>
+                <TableCell align='right'>{calculateWPM(entry.result, false)}</TableCell>
+                <TableCell align='right'>{calculateAccuracy(entry.result, false)}%</TableCell>
+                <TableCell align='right'>
+                  <IconButton size='small' onClick={() => deleteEntry(entry.id)}>
+                    <Delete fontSize='small' />
+                  </IconButton>
+                </TableCell>
+              </TableRow>
+            ))}
+          </TableBody>
+        </Table>
+        <TablePagination
+          component='div'
+          count={historyPage.totalCount}
+          page={page}
+          rowsPerPage={ROWS_PER_PAGE}
+          rowsPerPageOptions={[ROWS_PER_PAGE]}
+          onPageChange={(_, page) => loadPage(page)}
+        />
+      </DialogContent>
+      <DialogActions>
+        <Button component='label'>
+          インポート
+          <input type='file' accept='application/json' hidden onChange={importStatistics} />
+        </Button>
+        <Button onClick={() => exportStatistics(export_statistics_json, 'statistics.json', 'application/json')}>JSON</Button>
+        <Button onClick={() => exportStatistics(export_history_csv, 'history.csv', 'text/csv')}>履歴CSV</Button>
+        <Button onClick={() => exportStatistics(export_key_stroke_skills_csv, 'key_stroke_skills.csv', 'text/csv')}>キー別CSV</Button>
+        <Button onClick={() => exportStatistics(export_confusion_table_csv, 'confusion_table.csv', 'text/csv')}>ミスタイプCSV</Button>
+      </DialogActions>
+    </Dialog>
+  );
+}
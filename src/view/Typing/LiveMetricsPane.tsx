@@ -0,0 +1,16 @@
+import { Stack, Typography } from '@mui/material';
+import React from 'react';
+import { LiveMetrics } from '@/@types/type';
+
+export function LiveMetricsPane(props: { liveMetrics: LiveMetrics }): React.JSX.Element {
+  const { kpm, rollingKpm, accuracy, missedCount, estimatedRemainingTimeMs } = props.liveMetrics;
+
+  return (
+    <Stack spacing={1}>
+      <Typography variant='body1'>KPM: {Math.floor(kpm)}（直近 {Math.floor(rollingKpm)}）</Typography>
+      <Typography variant='body1'>正確率: {Math.floor(accuracy * 100)}%</Typography>
+      <Typography variant='body1'>ミス: {missedCount}</Typography>
+      <Typography variant='body1'>残り予想: {estimatedRemainingTimeMs === null ? '-' : `${(estimatedRemainingTimeMs / 1000).toFixed(1)}秒`}</Typography>
+    </Stack>
+  );
+}
@@ -2,15 +2,16 @@ import _, { useEffect, useRef } from 'react';
 import { TimerPane } from './TimerPane';
 import { ViewPane } from './ViewPane';
 import { KeyStrokePane } from './KeyStrokePane';
+import { LiveMetricsPane } from './LiveMetricsPane';
 import { useMilliSecondTimer } from '@/hook/useMilliSecondTimer';
 import { Grid, LinearProgress, Stack, styled, Typography } from '@mui/material';
 import { linearProgressClasses } from '@mui/material/LinearProgress';
 import { trackPageView } from '@/util/analyticsUtils';
 import { FixedFullScreenLayout } from '@/layout/FixedFullScreen';
-import { CancelGame, MayFinishPromise, OnInput } from '@/hook/useGameControl';
+import { CancelGame, MayFinishPromise, OnElapse, OnInput } from '@/hook/useGameControl';
 import { DisplayInfo } from '@/@types/type';
 
-export function TypingView(props: { displayInfo: DisplayInfo, onInput: OnInput, cancelGame: CancelGame, mayFinishPromise: MayFinishPromise }) {
+export function TypingView(props: { displayInfo: DisplayInfo, onInput: OnInput, onElapse: OnElapse, cancelGame: CancelGame, mayFinishPromise: MayFinishPromise }) {
   const [elapsedTime, startTimer, stopTimer, cancelTimer] = useMilliSecondTimer();
   const isStarted = useRef(false);
 
@@ -53,6 +54,12 @@ export function TypingView(props: { displayInfo: DisplayInfo, onInput: OnInput,
     }
   }, []);
 
+  useEffect(() => {
+    if (isStarted.current) {
+      props.onElapse(elapsedTime);
+    }
+  }, [elapsedTime]);
+
   useEffect(() => {
     addEventListener('keydown', handleKeyDown);
 
@@ -104,6 +111,9 @@ export function TypingView(props: { displayInfo: DisplayInfo, onInput: OnInput,
                 <Grid size={9} height={'100%'}>
                   <KeyStrokePane keyStrokeDisplayInfo={keyStrokeDisplayInfo} />
                 </Grid>
+                <Grid size={3}>
+                  <LiveMetricsPane liveMetrics={keyStrokeDisplayInfo.liveMetrics} />
+                </Grid>
               </Grid>
             </Grid>
           </Stack>
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use tsify::Tsify;
-use typing_engine::{display_info::ViewDisplayInfo, DisplayInfo};
+use typing_engine::{display_info::ViewDisplayInfo, DisplayInfo, EntitySummaryStatistics};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Tsify)]
 #[serde(rename_all = "camelCase")]
@@ -11,6 +12,57 @@ pub struct DisplayInformation {
     key_stroke: KeyStrokeDisplayInformation,
 }
 
+impl DisplayInformation {
+    pub(crate) fn with_live_metrics(mut self, live_metrics: LiveMetrics) -> Self {
+        self.key_stroke.live_metrics = live_metrics;
+
+        self
+    }
+
+    /// Keeps only laps whose end position in the view satisfies `is_kept`.
+    /// `view_lap_end_positions` must be the ones of the display information.
+    pub(crate) fn retain_laps(
+        mut self,
+        view_lap_end_positions: &[usize],
+        is_kept: impl Fn(usize) -> bool,
+    ) -> Self {
+        let kept: Vec<bool> = view_lap_end_positions
+            .iter()
+            .map(|&position| is_kept(position))
+            .collect();
+
+        // Lap end time exists only for finished laps, which come before unfinished laps
+        retain_by_index(&mut self.key_stroke.lap_end_positions, &kept);
+        retain_by_index(&mut self.key_stroke.lap_end_time, &kept);
+
+        self
+    }
+}
+
+impl DisplayInformation {
+    pub(crate) fn current_cursor_position(&self) -> usize {
+        self.key_stroke.current_cursor_position
+    }
+
+    pub(crate) fn missed_positions(&self) -> &[usize] {
+        &self.key_stroke.missed_positions
+    }
+
+    pub(crate) fn lap_end_positions(&self) -> &[usize] {
+        &self.key_stroke.lap_end_positions
+    }
+
+    pub(crate) fn lap_end_time(&self) -> &[f64] {
+        &self.key_stroke.lap_end_time
+    }
+}
+
+/// Keeps values whose index is marked as kept
+fn retain_by_index<T>(values: &mut Vec<T>, kept: &[bool]) {
+    let mut kept = kept.iter();
+    values.retain(|_| kept.next().copied().unwrap_or(false));
+}
+
 impl From<DisplayInfo> for DisplayInformation {
     fn from(di: DisplayInfo) -> Self {
         let key_stroke_info = di.key_stroke_info();
@@ -29,6 +81,7 @@ impl From<DisplayInfo> for DisplayInformation {
                     .iter()
                     .map(|d| d.as_millis() as f64)
                     .collect(),
+                live_metrics: LiveMetrics::default(),
             },
         }
     }
@@ -65,4 +118,66 @@ pub(crate) struct KeyStrokeDisplayInformation {
     progress: f64,
     lap_end_positions: Vec<usize>,
     lap_end_time: Vec<f64>,
+    live_metrics: LiveMetrics,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// A struct representing speed and accuracy of the game so far
+pub(crate) struct LiveMetrics {
+    /// Elapsed time excluding pauses
+    elapsed_time_ms: u64,
+    /// Key strokes per minute over the whole game so far
+    kpm: f64,
+    /// Key strokes per minute over the recent key strokes
+    rolling_kpm: f64,
+    /// Ratio of key strokes typed without any miss to finished key strokes
+    accuracy: f64,
+    missed_count: usize,
+    /// Time until the game finishes at the current speed, which is unknown before typing
+    estimated_remaining_time_ms: Option<u64>,
+}
+
+impl LiveMetrics {
+    /// Calculates metrics from summary of key strokes.
+    /// Time limited game finishes at `time_limit` even if query is left.
+    pub(crate) fn new(
+        summary: &EntitySummaryStatistics,
+        elapsed_time: Duration,
+        rolling_kpm: f64,
+        time_limit: Option<Duration>,
+    ) -> Self {
+        let elapsed_time_ms = elapsed_time.as_millis() as u64;
+        let finished_count = summary.finished_count();
+
+        let kpm = if elapsed_time_ms == 0 {
+            0.0
+        } else {
+            finished_count as f64 * 60000.0 / elapsed_time_ms as f64
+        };
+        let accuracy = if finished_count == 0 {
+            0.0
+        } else {
+            summary.completely_correct_count() as f64 / finished_count as f64
+        };
+
+        let remaining_count = summary.whole_count().saturating_sub(finished_count);
+        let estimated_remaining_time_ms = (kpm > 0.0)
+            .then(|| (remaining_count as f64 * 60000.0 / kpm) as u64)
+            .map(|estimated| match time_limit {
+                Some(time_limit) => {
+                    estimated.min((time_limit.as_millis() as u64).saturating_sub(elapsed_time_ms))
+                }
+                None => estimated,
+            });
+
+        Self {
+            elapsed_time_ms,
+            kpm,
+            rolling_kpm,
+            accuracy,
+            missed_count: summary.wrong_count(),
+            estimated_remaining_time_ms,
+        }
+    }
 }
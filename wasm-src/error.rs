@@ -0,0 +1,342 @@
+use serde::Serialize;
+use tsify::Tsify;
+use typing_engine::TypingEngineError;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::library::dictionary::{DictionaryOrigin, DictionaryType};
+use crate::library::SentenceRange;
+use crate::session::GamePhase;
+
+#[derive(Debug)]
+/// Error kind from WebAssembly
+pub(crate) enum WasmErrorKind {
+    CannotGetWindow,
+    CannotSerDe(serde_wasm_bindgen::Error),
+    JsError(JsValue),
+    StrokedKeyInvalid(String),
+    TypingEngineError(TypingEngineError),
+    CannotGetStorage,
+    CannotSerDeJson(serde_json::Error),
+    InvalidDictionaryName(String),
+    UserDictionaryNotFound(String),
+    DictionaryNameConflict(String),
+    NoDictionarySelected,
+    UnknownDictionary(DictionaryType, DictionaryOrigin, String),
+    EmptyVocabulary,
+    IllegalGameTransition(GamePhase, &'static str),
+    UnsupportedStatisticsVersion(u32),
+    HistoryEntryNotFound(String),
+    InvalidSentenceRange(SentenceRange, usize),
+    CannotContinueFromBookmark,
+    InvalidDate(String),
+    NoMissedVocabulary,
+    InvalidTimeLimit(u32, u32),
+    InternalError(String),
+}
+
+impl WasmErrorKind {
+    /// Returns stable error code of this kind
+    fn code(&self) -> WasmErrorCode {
+        match self {
+            Self::CannotGetWindow => WasmErrorCode::CannotGetWindow,
+            Self::CannotSerDe(_) | Self::CannotSerDeJson(_) => WasmErrorCode::SerializationFailed,
+            Self::JsError(_) => WasmErrorCode::JsError,
+            Self::StrokedKeyInvalid(_) => WasmErrorCode::InvalidKeyStroke,
+            // typing_engine only exposes the kind of its error through the message
+            Self::TypingEngineError(err) => match err.to_string().as_str() {
+                "not initialized" => WasmErrorCode::EngineNotInitialized,
+                "not started" => WasmErrorCode::EngineNotStarted,
+                "already finished" => WasmErrorCode::EngineAlreadyFinished,
+                "not finished" => WasmErrorCode::EngineNotFinished,
+                _ => WasmErrorCode::EngineError,
+            },
+            Self::CannotGetStorage => WasmErrorCode::CannotGetStorage,
+            Self::InvalidDictionaryName(_) => WasmErrorCode::InvalidDictionaryName,
+            Self::UserDictionaryNotFound(_) => WasmErrorCode::UserDictionaryNotFound,
+            Self::DictionaryNameConflict(_) => WasmErrorCode::DictionaryNameConflict,
+            Self::NoDictionarySelected => WasmErrorCode::NoDictionarySelected,
+            Self::UnknownDictionary(..) => WasmErrorCode::UnknownDictionary,
+            Self::EmptyVocabulary => WasmErrorCode::EmptyVocabulary,
+            Self::IllegalGameTransition(..) => WasmErrorCode::IllegalGameTransition,
+            Self::UnsupportedStatisticsVersion(_) => WasmErrorCode::UnsupportedStatisticsVersion,
+            Self::HistoryEntryNotFound(_) => WasmErrorCode::HistoryEntryNotFound,
+            Self::InvalidSentenceRange(..) => WasmErrorCode::InvalidSentenceRange,
+            Self::CannotContinueFromBookmark => WasmErrorCode::CannotContinueFromBookmark,
+            Self::InvalidDate(_) => WasmErrorCode::InvalidDate,
+            Self::NoMissedVocabulary => WasmErrorCode::NoMissedVocabulary,
+            Self::InvalidTimeLimit(..) => WasmErrorCode::InvalidTimeLimit,
+            Self::InternalError(_) => WasmErrorCode::InternalError,
+        }
+    }
+
+    /// Returns human readable message of this kind
+    fn message(&self) -> String {
+        match self {
+            Self::CannotGetWindow => "Cannot get global `window`".to_string(),
+            Self::CannotSerDe(err) => format!("Cannot (de)serialize value: {}", err),
+            Self::JsError(value) => describe_js_value(value),
+            Self::StrokedKeyInvalid(key) => format!("Invalid key stroke: {}", key),
+            Self::TypingEngineError(err) => format!("Typing engine is {}", err),
+            Self::CannotGetStorage => "Cannot get browser storage".to_string(),
+            Self::CannotSerDeJson(err) => format!("Cannot (de)serialize JSON: {}", err),
+            Self::InvalidDictionaryName(name) => format!("Invalid dictionary name: {:?}", name),
+            Self::UserDictionaryNotFound(name) => format!("User dictionary not found: {}", name),
+            Self::DictionaryNameConflict(name) => format!("Dictionary already exists: {}", name),
+            Self::NoDictionarySelected => "No dictionary is selected".to_string(),
+            Self::UnknownDictionary(dictionary_type, origin, name) => format!(
+                "Unknown dictionary: {} ({:?}, {:?})",
+                name, dictionary_type, origin
+            ),
+            Self::EmptyVocabulary => "Selected dictionaries have no valid vocabulary".to_string(),
+            Self::IllegalGameTransition(phase, action) => {
+                format!("`{}` cannot be called when game is {:?}", action, phase)
+            }
+            Self::UnsupportedStatisticsVersion(version) => {
+                format!("Statistics schema version {} is not supported", version)
+            }
+            Self::HistoryEntryNotFound(id) => format!("History entry not found: {}", id),
+            Self::InvalidSentenceRange(range, vocabulary_count) => format!(
+                "Sentence range {:?} is invalid for {} sentences",
+                range, vocabulary_count
+            ),
+            Self::CannotContinueFromBookmark => {
+                "Continuing from bookmark requires exactly one sentence dictionary".to_string()
+            }
+            Self::InvalidDate(date) => format!("Date must be in YYYY-MM-DD format: {}", date),
+            Self::NoMissedVocabulary => "No vocabulary is missed in the last game".to_string(),
+            Self::InvalidTimeLimit(time_limit_sec, max_time_limit_sec) => format!(
+                "Time limit must be at most {} seconds: {}",
+                max_time_limit_sec, time_limit_sec
+            ),
+            Self::InternalError(err) => format!("Internal error: {}", err),
+        }
+    }
+
+    /// Returns additional information of this kind if exists
+    fn details(&self) -> Option<WasmErrorDetails> {
+        match self {
+            Self::StrokedKeyInvalid(key) => Some(WasmErrorDetails {
+                key: Some(key.clone()),
+                ..Default::default()
+            }),
+            Self::InvalidDictionaryName(name)
+            | Self::UserDictionaryNotFound(name)
+            | Self::DictionaryNameConflict(name) => Some(WasmErrorDetails {
+                dictionary_name: Some(name.clone()),
+                ..Default::default()
+            }),
+            Self::UnknownDictionary(dictionary_type, origin, name) => Some(WasmErrorDetails {
+                dictionary_name: Some(name.clone()),
+                dictionary_type: Some(*dictionary_type),
+                dictionary_origin: Some(*origin),
+                ..Default::default()
+            }),
+            Self::IllegalGameTransition(phase, _) => Some(WasmErrorDetails {
+                game_phase: Some(*phase),
+                ..Default::default()
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "snake_case")]
+/// Stable code identifying the kind of error thrown from WebAssembly
+pub enum WasmErrorCode {
+    CannotGetWindow,
+    CannotGetStorage,
+    SerializationFailed,
+    JsError,
+    InvalidKeyStroke,
+    EngineNotInitialized,
+    EngineNotStarted,
+    EngineAlreadyFinished,
+    EngineNotFinished,
+    EngineError,
+    InvalidDictionaryName,
+    UserDictionaryNotFound,
+    DictionaryNameConflict,
+    NoDictionarySelected,
+    UnknownDictionary,
+    EmptyVocabulary,
+    IllegalGameTransition,
+    UnsupportedStatisticsVersion,
+    HistoryEntryNotFound,
+    InvalidSentenceRange,
+    CannotContinueFromBookmark,
+    InvalidDate,
+    NoMissedVocabulary,
+    InvalidTimeLimit,
+    InternalError,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+/// Additional information of error thrown from WebAssembly
+pub struct WasmErrorDetails {
+    /// Key which caused the error
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    /// Name of the dictionary which caused the error
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dictionary_name: Option<String>,
+    /// Type of the dictionary which caused the error
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dictionary_type: Option<DictionaryType>,
+    /// Origin of the dictionary which caused the error
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dictionary_origin: Option<DictionaryOrigin>,
+    /// Phase of game when the error occurred
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    game_phase: Option<GamePhase>,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+/// Error object thrown from WebAssembly to JavaScript
+pub struct WasmErrorObject {
+    code: WasmErrorCode,
+    message: String,
+    #[tsify(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<WasmErrorDetails>,
+}
+
+impl From<&WasmErrorKind> for WasmErrorObject {
+    fn from(value: &WasmErrorKind) -> Self {
+        Self {
+            code: value.code(),
+            message: value.message(),
+            details: value.details(),
+        }
+    }
+}
+
+impl From<WasmErrorKind> for JsValue {
+    fn from(value: WasmErrorKind) -> Self {
+        let error_object = WasmErrorObject::from(&value);
+
+        serde_wasm_bindgen::to_value(&error_object)
+            .unwrap_or_else(|_| JsValue::from_str(&error_object.message))
+    }
+}
+
+#[derive(Debug)]
+/// Error from WebAssembly
+pub struct WasmError {
+    kind: WasmErrorKind,
+}
+
+impl WasmError {
+    pub(crate) fn new(kind: WasmErrorKind) -> Self {
+        Self { kind }
+    }
+
+    pub(crate) fn message(&self) -> String {
+        self.kind.message()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn kind(&self) -> &WasmErrorKind {
+        &self.kind
+    }
+}
+
+impl From<JsValue> for WasmError {
+    fn from(value: JsValue) -> Self {
+        WasmError {
+            kind: WasmErrorKind::JsError(value),
+        }
+    }
+}
+
+impl From<serde_wasm_bindgen::Error> for WasmError {
+    fn from(value: serde_wasm_bindgen::Error) -> Self {
+        WasmError {
+            kind: WasmErrorKind::CannotSerDe(value),
+        }
+    }
+}
+
+impl From<serde_json::Error> for WasmError {
+    fn from(value: serde_json::Error) -> Self {
+        WasmError {
+            kind: WasmErrorKind::CannotSerDeJson(value),
+        }
+    }
+}
+
+impl From<TypingEngineError> for WasmError {
+    fn from(value: TypingEngineError) -> Self {
+        WasmError {
+            kind: WasmErrorKind::TypingEngineError(value),
+        }
+    }
+}
+
+impl From<WasmError> for JsValue {
+    fn from(value: WasmError) -> Self {
+        value.kind.into()
+    }
+}
+
+/// Returns human readable description of thrown JavaScript value.
+/// Name and message are used for errors and DOM exceptions (e.g. `QuotaExceededError` from
+/// browser storage), and debug representation is used only for other values.
+pub(crate) fn describe_js_value(value: &JsValue) -> String {
+    if let Some(message) = value.as_string() {
+        return message;
+    }
+
+    if let Some(exception) = value.dyn_ref::<web_sys::DomException>() {
+        return format!("{}: {}", exception.name(), exception.message());
+    }
+
+    if let Some(error) = value.dyn_ref::<js_sys::Error>() {
+        return format!(
+            "{}: {}",
+            String::from(error.name()),
+            String::from(error.message())
+        );
+    }
+
+    format!("{:?}", value)
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroUsize;
+
+    use typing_engine::{LapRequest, TypingEngine};
+
+    use super::*;
+
+    #[test]
+    fn typing_engine_error_is_mapped_to_stable_code() {
+        let mut typing_engine = TypingEngine::new();
+
+        let err = typing_engine
+            .construct_display_info(LapRequest::IdealKeyStroke(NonZeroUsize::new(50).unwrap()))
+            .unwrap_err();
+        assert_eq!(
+            WasmErrorKind::TypingEngineError(err).code(),
+            WasmErrorCode::EngineNotStarted
+        );
+
+        let err = typing_engine.start().unwrap_err();
+        assert_eq!(
+            WasmErrorKind::TypingEngineError(err).code(),
+            WasmErrorCode::EngineNotInitialized
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroUsize;
+use tsify::Tsify;
+use typing_engine::LapRequest;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(tag = "type", rename_all = "snake_case")]
+/// Represents how typing is divided into laps
+pub enum LapPolicy {
+    /// A lap for every `count` ideal key strokes
+    IdealKeyStroke { count: NonZeroUsize },
+    /// A lap for every `count` actual key strokes including wrong ones
+    KeyStroke { count: NonZeroUsize },
+    /// A lap for every vocabulary entry
+    Vocabulary,
+}
+
+impl Default for LapPolicy {
+    fn default() -> Self {
+        Self::IdealKeyStroke {
+            count: NonZeroUsize::new(50).unwrap(),
+        }
+    }
+}
+
+impl LapPolicy {
+    /// Returns lap request passed to typing engine.
+    /// Typing engine has no lap for vocabulary, so laps of every chunk are requested and only the
+    /// laps ending at the end of vocabularies should be kept.
+    pub(crate) fn lap_request(&self) -> LapRequest {
+        match self {
+            Self::IdealKeyStroke { count } => LapRequest::IdealKeyStroke(*count),
+            Self::KeyStroke { count } => LapRequest::KeyStroke(*count),
+            Self::Vocabulary => LapRequest::Chunk(NonZeroUsize::new(1).unwrap()),
+        }
+    }
+}
@@ -1,108 +1,30 @@
 use display::DisplayInformation;
-use library::dictionary::DictionaryType;
-use library::Library;
+pub use error::WasmError;
+use lap::LapPolicy;
+use library::dictionary::{DictionaryInfo, DictionaryOrigin, DictionaryType};
+use library::LibraryHandle;
 use library::{dictionary::DictionaryCatalog, QueryRequestFromUI};
 use result::GameResult;
-use serde::{Deserialize, Serialize};
-use std::num::NonZeroUsize;
+use session::{
+    DailyChallengeHistory, GamePhase, HistoryPage, KeyStrokeInfo, StatisticsImportSummary,
+    StrokeKeyResult, TypingSession,
+};
 use std::sync::LazyLock;
-use std::time::Duration;
 use tokio::sync::Mutex;
-use tsify::Tsify;
-use typing_engine::{
-    KeyStrokeCharError, LapRequest, QueryRequest, TypingEngine, TypingResult as LibTypingResult,
-    VocabularyOrder, VocabularyQuantifier, VocabularySeparator,
-};
 use wasm_bindgen::prelude::*;
 
 mod display;
+mod error;
+mod lap;
 mod library;
 mod result;
+mod session;
 mod utils;
 
-static LIBRARY: LazyLock<Mutex<Library>> = LazyLock::new(|| Mutex::new(Library::new()));
-static TYPING_ENGINE: LazyLock<Mutex<TypingEngine>> =
-    LazyLock::new(|| Mutex::new(TypingEngine::new()));
-static AGGREGATED_RESULT: LazyLock<Mutex<Option<LibTypingResult>>> =
-    LazyLock::new(|| Mutex::new(None));
-
-#[derive(Debug)]
-/// Error kind from WebAssembly
-enum WasmErrorKind {
-    CannotGetWindow,
-    CannotSerDe(serde_wasm_bindgen::Error),
-    JsError(JsValue),
-    StrokedKeyInvalid(String),
-    TypingEngineError(typing_engine::TypingEngineError),
-    InternalError(String),
-}
-
-impl From<WasmErrorKind> for JsValue {
-    fn from(value: WasmErrorKind) -> Self {
-        match value {
-            WasmErrorKind::CannotGetWindow => JsValue::from_str("Cannot get global `window`"),
-            WasmErrorKind::JsError(value) => value,
-            WasmErrorKind::CannotSerDe(err) => err.into(),
-            WasmErrorKind::StrokedKeyInvalid(key) => {
-                JsValue::from_str(&format!("Invalid key stroke: {}", key))
-            }
-            WasmErrorKind::TypingEngineError(err) => JsValue::from_str(&format!("{:?}", err)),
-            WasmErrorKind::InternalError(err) => {
-                JsValue::from_str(&format!("Internal error: {}", err))
-            }
-        }
-    }
-}
-
-#[derive(Debug)]
-/// Error from WebAssembly
-pub struct WasmError {
-    kind: WasmErrorKind,
-}
-
-impl WasmError {
-    fn new(kind: WasmErrorKind) -> Self {
-        Self { kind }
-    }
-}
-
-impl From<JsValue> for WasmError {
-    fn from(value: JsValue) -> Self {
-        WasmError {
-            kind: WasmErrorKind::JsError(value),
-        }
-    }
-}
-
-impl From<serde_wasm_bindgen::Error> for WasmError {
-    fn from(value: serde_wasm_bindgen::Error) -> Self {
-        WasmError {
-            kind: WasmErrorKind::CannotSerDe(value),
-        }
-    }
-}
-
-impl From<typing_engine::TypingEngineError> for WasmError {
-    fn from(value: typing_engine::TypingEngineError) -> Self {
-        WasmError {
-            kind: WasmErrorKind::TypingEngineError(value),
-        }
-    }
-}
-
-impl From<KeyStrokeCharError> for WasmError {
-    fn from(value: KeyStrokeCharError) -> Self {
-        Self {
-            kind: WasmErrorKind::StrokedKeyInvalid(value.to_string()),
-        }
-    }
-}
-
-impl From<WasmError> for JsValue {
-    fn from(value: WasmError) -> Self {
-        value.kind.into()
-    }
-}
+// Free functions exported to JavaScript are thin wrappers over these default instances
+static DEFAULT_LIBRARY: LazyLock<LibraryHandle> = LazyLock::new(LibraryHandle::new);
+static DEFAULT_SESSION: LazyLock<Mutex<TypingSession>> =
+    LazyLock::new(|| Mutex::new(TypingSession::new()));
 
 #[wasm_bindgen]
 extern "C" {
@@ -118,148 +40,194 @@ pub fn main() -> Result<(), JsValue> {
 
 #[wasm_bindgen]
 pub async fn get_dictionary_catalog() -> Result<DictionaryCatalog, WasmError> {
-    let window = web_sys::window().ok_or(WasmError::new(WasmErrorKind::CannotGetWindow))?;
-    let mut library = LIBRARY.lock().await;
+    DEFAULT_LIBRARY.get_dictionary_catalog().await
+}
 
-    library.load(&window).await?;
+#[wasm_bindgen]
+pub fn import_user_dictionary(
+    name: String,
+    dictionary_type: DictionaryType,
+    body: String,
+) -> Result<DictionaryInfo, WasmError> {
+    let dictionary_info =
+        DEFAULT_LIBRARY.import_user_dictionary(name.clone(), dictionary_type, body)?;
+    DEFAULT_SESSION
+        .blocking_lock()
+        .remove_user_dictionary_bookmark(dictionary_type, &name)?;
 
-    Ok(library.construct_dictionary_catalog())
+    Ok(dictionary_info)
 }
 
 #[wasm_bindgen]
-pub fn confirm_query(query_request: QueryRequestFromUI) -> Result<(), WasmError> {
-    let library = LIBRARY.blocking_lock();
-
-    let vocabulary_entries = library.construct_vocabulary_entries_for_request(&query_request);
-
-    let request = match query_request.dictionary_type() {
-        DictionaryType::Word => QueryRequest::new(
-            &vocabulary_entries,
-            VocabularyQuantifier::KeyStroke(
-                query_request
-                    .key_stroke_count_threshold()
-                    .unwrap_or(NonZeroUsize::new(150).unwrap()),
-            ),
-            VocabularySeparator::WhiteSpace,
-            VocabularyOrder::Random,
-        ),
-        DictionaryType::Sentence => QueryRequest::new(
-            &vocabulary_entries,
-            VocabularyQuantifier::Vocabulary(
-                query_request
-                    .key_stroke_count_threshold()
-                    .unwrap_or(NonZeroUsize::new(vocabulary_entries.len()).unwrap()),
-            ),
-            VocabularySeparator::None,
-            VocabularyOrder::InOrder,
-        ),
-    };
-
-    let mut typing_engine = TYPING_ENGINE.blocking_lock();
-
-    typing_engine.init(request);
+pub fn delete_user_dictionary(
+    dictionary_type: DictionaryType,
+    name: String,
+) -> Result<(), WasmError> {
+    DEFAULT_LIBRARY.delete_user_dictionary(dictionary_type, name.clone())?;
+    DEFAULT_SESSION
+        .blocking_lock()
+        .remove_user_dictionary_bookmark(dictionary_type, &name)
+}
 
-    Ok(())
+#[wasm_bindgen]
+pub fn rename_user_dictionary(
+    dictionary_type: DictionaryType,
+    name: String,
+    new_name: String,
+) -> Result<DictionaryInfo, WasmError> {
+    let dictionary_info =
+        DEFAULT_LIBRARY.rename_user_dictionary(dictionary_type, name.clone(), new_name.clone())?;
+    DEFAULT_SESSION
+        .blocking_lock()
+        .rename_user_dictionary_bookmark(dictionary_type, &name, &new_name)?;
+
+    Ok(dictionary_info)
 }
 
 #[wasm_bindgen]
-pub fn start_game() -> Result<DisplayInformation, WasmError> {
-    let mut typing_engine = TYPING_ENGINE.blocking_lock();
+pub fn get_lap_policy() -> LapPolicy {
+    DEFAULT_SESSION.blocking_lock().get_lap_policy()
+}
 
-    typing_engine.start()?;
+#[wasm_bindgen]
+pub fn set_lap_policy(lap_policy: LapPolicy) -> Result<(), WasmError> {
+    DEFAULT_SESSION.blocking_lock().set_lap_policy(lap_policy)
+}
 
-    Ok(typing_engine
-        .construct_display_info(LapRequest::IdealKeyStroke(NonZeroUsize::new(50).unwrap()))?
-        .into())
+#[wasm_bindgen]
+pub fn confirm_query(query_request: QueryRequestFromUI) -> Result<Option<u32>, WasmError> {
+    DEFAULT_SESSION
+        .blocking_lock()
+        .confirm_query(&DEFAULT_LIBRARY, query_request)
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Tsify)]
-#[tsify(from_wasm_abi)]
-#[serde(rename_all = "camelCase")]
-/// A struct representing the information of a key stroke
-pub struct KeyStrokeInfo {
-    key: String,
-    elapsed_time_ms: u64,
+#[wasm_bindgen]
+pub fn confirm_daily_challenge(date: String) -> Result<(), WasmError> {
+    DEFAULT_SESSION
+        .blocking_lock()
+        .confirm_daily_challenge(&DEFAULT_LIBRARY, date)
 }
 
-#[derive(Debug, Clone, Serialize, Tsify)]
-#[tsify(into_wasm_abi)]
-#[serde(rename_all = "camelCase")]
-/// A struct repsenting the result of a key stroke
-pub struct StrokeKeyResult {
-    is_finished: bool,
-    display_information: DisplayInformation,
+#[wasm_bindgen]
+pub fn confirm_missed_vocabularies() -> Result<(), WasmError> {
+    DEFAULT_SESSION
+        .blocking_lock()
+        .confirm_missed_vocabularies()
+}
+
+#[wasm_bindgen]
+pub fn get_game_state() -> GamePhase {
+    DEFAULT_SESSION.blocking_lock().get_game_state()
+}
+
+#[wasm_bindgen]
+pub fn start_game() -> Result<DisplayInformation, WasmError> {
+    DEFAULT_SESSION.blocking_lock().start_game()
 }
 
 #[wasm_bindgen]
 pub fn stroke_key(key_stroke_info: KeyStrokeInfo) -> Result<StrokeKeyResult, WasmError> {
-    let mut typing_engine = TYPING_ENGINE.blocking_lock();
+    DEFAULT_SESSION.blocking_lock().stroke_key(key_stroke_info)
+}
 
-    let elapsed_time_duration = Duration::from_millis(key_stroke_info.elapsed_time_ms);
+#[wasm_bindgen]
+pub fn check_time_limit(elapsed_time_ms: u64) -> Result<bool, WasmError> {
+    DEFAULT_SESSION
+        .blocking_lock()
+        .check_time_limit(elapsed_time_ms)
+}
 
-    if key_stroke_info.key.chars().count() != 1 {
-        return Err(WasmError::new(WasmErrorKind::StrokedKeyInvalid(
-            key_stroke_info.key,
-        )));
-    }
-    let key_stroke_char = key_stroke_info.key.chars().next().unwrap();
+#[wasm_bindgen]
+pub fn get_result() -> Result<GameResult, WasmError> {
+    DEFAULT_SESSION.blocking_lock().get_result()
+}
 
-    let is_finished = typing_engine
-        .stroke_key_with_elapsed_time(key_stroke_char.try_into()?, elapsed_time_duration)?;
+#[wasm_bindgen]
+pub fn pause_game() -> Result<(), WasmError> {
+    DEFAULT_SESSION.blocking_lock().pause_game()
+}
 
-    let display_information: DisplayInformation = typing_engine
-        .construct_display_info(LapRequest::IdealKeyStroke(NonZeroUsize::new(50).unwrap()))?
-        .into();
+#[wasm_bindgen]
+pub fn resume_game() -> Result<(), WasmError> {
+    DEFAULT_SESSION.blocking_lock().resume_game()
+}
 
-    if is_finished {
-        update_aggregated_result(&mut typing_engine)?;
-    }
+#[wasm_bindgen]
+pub fn abort_game(merge_into_aggregated: bool) -> Result<GameResult, WasmError> {
+    DEFAULT_SESSION
+        .blocking_lock()
+        .abort_game(merge_into_aggregated)
+}
 
-    Ok(StrokeKeyResult {
-        is_finished,
-        display_information,
-    })
+#[wasm_bindgen]
+pub fn reset_statistics() -> Result<(), WasmError> {
+    DEFAULT_SESSION.blocking_lock().reset_statistics()
 }
 
-fn update_aggregated_result(typing_engine: &mut TypingEngine) -> Result<(), WasmError> {
-    let this_result = typing_engine
-        .construct_result(LapRequest::IdealKeyStroke(NonZeroUsize::new(50).unwrap()))?;
+#[wasm_bindgen]
+pub fn get_history() -> HistoryPage {
+    DEFAULT_SESSION.blocking_lock().get_history()
+}
 
-    let mut aggregated_result = AGGREGATED_RESULT.blocking_lock();
+#[wasm_bindgen]
+pub fn get_history_page(offset: usize, limit: usize) -> HistoryPage {
+    DEFAULT_SESSION
+        .blocking_lock()
+        .get_history_page(offset, limit)
+}
 
-    let new_aggregated_result = if let Some(aggregated_result) = aggregated_result.as_mut() {
-        aggregated_result.clone() + this_result
-    } else {
-        this_result
-    };
+#[wasm_bindgen]
+pub fn delete_history_entry(id: String) -> Result<(), WasmError> {
+    DEFAULT_SESSION.blocking_lock().delete_history_entry(id)
+}
 
-    aggregated_result.replace(new_aggregated_result);
+#[wasm_bindgen]
+pub fn get_bookmark(dictionary_origin: DictionaryOrigin, name: String) -> Option<usize> {
+    DEFAULT_SESSION
+        .blocking_lock()
+        .get_bookmark(dictionary_origin, name)
+}
 
-    Ok(())
+#[wasm_bindgen]
+pub fn get_daily_challenge_history() -> DailyChallengeHistory {
+    DEFAULT_SESSION
+        .blocking_lock()
+        .get_daily_challenge_history()
 }
 
 #[wasm_bindgen]
-pub fn get_result() -> Result<GameResult, WasmError> {
-    let typing_engine = TYPING_ENGINE.blocking_lock();
+pub fn export_statistics_json() -> Result<String, WasmError> {
+    DEFAULT_SESSION.blocking_lock().export_statistics_json()
+}
 
-    let this_result = typing_engine
-        .construct_result(LapRequest::IdealKeyStroke(NonZeroUsize::new(50).unwrap()))?;
+#[wasm_bindgen]
+pub fn export_history_csv() -> String {
+    DEFAULT_SESSION.blocking_lock().export_history_csv()
+}
 
-    let aggregated_result = AGGREGATED_RESULT.blocking_lock().clone();
-    if aggregated_result.is_none() {
-        return Err(WasmError::new(WasmErrorKind::InternalError(
-            "Aggregated result is not initialized".to_string(),
-        )));
-    }
-    let aggregated_result = aggregated_result.unwrap();
+#[wasm_bindgen]
+pub fn export_key_stroke_skills_csv() -> String {
+    DEFAULT_SESSION
+        .blocking_lock()
+        .export_key_stroke_skills_csv()
+}
 
-    Ok(GameResult::new(this_result, aggregated_result))
+#[wasm_bindgen]
+pub fn export_confusion_table_csv() -> String {
+    DEFAULT_SESSION.blocking_lock().export_confusion_table_csv()
 }
 
 #[wasm_bindgen]
-pub fn reset_statistics() -> Result<(), WasmError> {
-    let mut aggregated_result = AGGREGATED_RESULT.blocking_lock();
-    aggregated_result.take();
+pub fn import_statistics_json(json: String) -> Result<StatisticsImportSummary, WasmError> {
+    DEFAULT_SESSION.blocking_lock().import_statistics_json(json)
+}
 
-    Ok(())
+#[wasm_bindgen]
+pub fn load_statistics() -> Result<(), WasmError> {
+    DEFAULT_SESSION.blocking_lock().load_statistics()
+}
+
+#[wasm_bindgen]
+pub fn save_statistics() -> Result<(), WasmError> {
+    DEFAULT_SESSION.blocking_lock().save_statistics()
 }
@@ -1,23 +1,30 @@
 use dictionary::{
-    Dictionary, DictionaryCatalog, DictionaryIndex, DictionaryOrigin, DictionaryType,
+    Dictionary, DictionaryCatalog, DictionaryIndex, DictionaryInfo, DictionaryLoadFailure,
+    DictionaryOrigin, DictionaryType,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_wasm_bindgen::from_value;
 use std::collections::HashMap;
-use std::num::NonZeroUsize;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::sync::Arc;
+use std::time::Duration;
+use storage::UserDictionaryRecord;
+use tokio::sync::{Mutex, MutexGuard};
 use tsify::Tsify;
-use typing_engine::VocabularyEntry;
+use typing_engine::{VocabularyEntry, VocabularySeparator};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::Response;
 
-use crate::WasmError;
+use crate::error::{WasmError, WasmErrorKind};
+use crate::utils::get_window;
 
 pub(crate) mod dictionary;
 #[cfg(test)]
 // This module is for testing dictionary file in public directory
 mod dictionary_validity;
+mod storage;
 
 /// A type alias for a dictionary map, where the key is a tuple of the dictionary origin and its
 /// name.
@@ -31,9 +38,133 @@ pub struct QueryRequestFromUI {
     dictionary_type: DictionaryType,
     used_dictionaries: Vec<(DictionaryOrigin, String)>,
     key_stroke_count_threshold: Option<NonZeroUsize>,
+    /// Time limit of the game in seconds, which must be at most 600 seconds.
+    /// When specified, game is finished when the time passes instead of typing whole query.
+    #[serde(default)]
+    #[tsify(optional)]
+    time_limit_sec: Option<NonZeroU32>,
+    /// Order of vocabularies in query.
+    /// When not specified, words are shuffled and sentences are in order of the dictionary.
+    #[serde(default)]
+    #[tsify(optional)]
+    order: Option<QueryOrder>,
+    /// Separator between vocabularies in query.
+    /// When not specified, words are separated by white space and sentences are not separated.
+    #[serde(default)]
+    #[tsify(optional)]
+    separator: Option<QuerySeparator>,
+    /// Range of sentences used for query, which is only for sentence dictionaries
+    #[serde(default)]
+    #[tsify(optional)]
+    sentence_range: Option<SentenceRange>,
+    /// Whether sentences are continued from the bookmark of the sentence dictionary.
+    /// When true, `sentence_range` is ignored and `key_stroke_count_threshold` is used as the
+    /// count of sentences.
+    #[serde(default)]
+    #[tsify(optional)]
+    continue_from_bookmark: bool,
+    /// Seed of random order.
+    /// When not specified, seed is generated randomly and returned from `confirm_query`.
+    #[serde(default)]
+    #[tsify(optional)]
+    seed: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(tag = "type", rename_all = "snake_case")]
+/// Represents a range of sentences in selected dictionaries.
+/// Sentences are numbered from 1 in order of valid entries of selected dictionaries.
+pub enum SentenceRange {
+    /// Sentences from `first` to `last`, both inclusive
+    Lines {
+        first: NonZeroUsize,
+        last: NonZeroUsize,
+    },
+    /// `count` sentences from `start`
+    From {
+        start: NonZeroUsize,
+        count: NonZeroUsize,
+    },
+}
+
+impl SentenceRange {
+    /// Returns number of the first sentence in the range
+    pub(crate) fn first(&self) -> NonZeroUsize {
+        match self {
+            Self::Lines { first, .. } => *first,
+            Self::From { start, .. } => *start,
+        }
+    }
+
+    /// Returns range of indices in entries, or `None` when it is out of `vocabulary_count`
+    fn to_index_range(self, vocabulary_count: usize) -> Option<std::ops::Range<usize>> {
+        let (first, last) = match self {
+            Self::Lines { first, last } => (first.get(), last.get()),
+            // Both are given by UI, so the last sentence can overflow
+            Self::From { start, count } => (start.get(), start.get().checked_add(count.get() - 1)?),
+        };
+
+        if first > last || last > vocabulary_count {
+            return None;
+        }
+
+        Some(first - 1..last)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "snake_case")]
+/// Represents how vocabularies are ordered in query
+pub enum QueryOrder {
+    /// Vocabularies are chosen randomly
+    Random,
+    /// Vocabularies are chosen in order of dictionaries
+    InOrder,
+    /// Vocabularies are chosen randomly, but vocabularies containing keys the user is slow at or
+    /// often misses are chosen more often according to aggregated result
+    Weakness,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "snake_case")]
+/// Represents what is inserted between vocabularies in query
+pub enum QuerySeparator {
+    None,
+    WhiteSpace,
+}
+
+impl From<QuerySeparator> for VocabularySeparator {
+    fn from(separator: QuerySeparator) -> Self {
+        match separator {
+            QuerySeparator::None => VocabularySeparator::None,
+            QuerySeparator::WhiteSpace => VocabularySeparator::WhiteSpace,
+        }
+    }
 }
 
 impl QueryRequestFromUI {
+    /// Constructs request which is not from UI, with optional fields left unspecified
+    pub(crate) fn new(
+        dictionary_type: DictionaryType,
+        used_dictionaries: Vec<(DictionaryOrigin, String)>,
+        key_stroke_count_threshold: Option<NonZeroUsize>,
+    ) -> Self {
+        Self {
+            dictionary_type,
+            used_dictionaries,
+            key_stroke_count_threshold,
+            time_limit_sec: None,
+            order: None,
+            separator: None,
+            sentence_range: None,
+            continue_from_bookmark: false,
+            seed: None,
+        }
+    }
+
     pub(crate) fn dictionary_type(&self) -> DictionaryType {
         self.dictionary_type
     }
@@ -45,12 +176,70 @@ impl QueryRequestFromUI {
     pub(crate) fn key_stroke_count_threshold(&self) -> Option<NonZeroUsize> {
         self.key_stroke_count_threshold
     }
+
+    pub(crate) fn order(&self) -> Option<QueryOrder> {
+        self.order
+    }
+
+    pub(crate) fn separator(&self) -> Option<QuerySeparator> {
+        self.separator
+    }
+
+    pub(crate) fn sentence_range(&self) -> Option<SentenceRange> {
+        self.sentence_range
+    }
+
+    pub(crate) fn seed(&self) -> Option<u32> {
+        self.seed
+    }
+
+    /// Returns request whose random order is fixed by the seed
+    pub(crate) fn with_seed(&self, seed: u32) -> Self {
+        Self {
+            seed: Some(seed),
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn continue_from_bookmark(&self) -> bool {
+        self.continue_from_bookmark
+    }
+
+    /// Returns request whose sentences are limited to the range.
+    /// Count of sentences is determined by the range, so threshold is cleared.
+    pub(crate) fn with_sentence_range(&self, sentence_range: SentenceRange) -> Self {
+        Self {
+            key_stroke_count_threshold: None,
+            sentence_range: Some(sentence_range),
+            continue_from_bookmark: false,
+            ..self.clone()
+        }
+    }
+
+    /// Returns request for all of the sentences in used dictionaries
+    pub(crate) fn without_sentence_range(&self) -> Self {
+        Self {
+            sentence_range: None,
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn time_limit(&self) -> Option<Duration> {
+        self.time_limit_sec
+            .map(|time_limit_sec| Duration::from_secs(time_limit_sec.get().into()))
+    }
 }
 
 /// A sruct responsible for loading and managing dictionaries.
 pub(crate) struct Library {
     word_dictionaries: DictionariesInLibrary,
     sentence_dictionaries: DictionariesInLibrary,
+    /// Source of user defined dictionaries which is synchronized with browser storage
+    user_dictionary_records: Vec<UserDictionaryRecord>,
+    /// Builtin dictionaries failed to load at the last loading
+    load_failures: Vec<DictionaryLoadFailure>,
+    /// Message of the error when user defined dictionaries cannot be restored at the last loading
+    user_dictionary_restore_error: Option<String>,
 }
 
 impl Library {
@@ -58,21 +247,183 @@ impl Library {
         Self {
             word_dictionaries: HashMap::new(),
             sentence_dictionaries: HashMap::new(),
+            user_dictionary_records: Vec::new(),
+            load_failures: Vec::new(),
+            user_dictionary_restore_error: None,
         }
     }
 
-    /// Loads the dictionaries
+    /// Loads the dictionaries.
+    /// Builtin dictionaries are fetched from the server and user defined dictionaries are restored
+    /// from browser storage.
+    /// Failure of restoring user defined dictionaries is reported in the catalog, so that builtin
+    /// dictionaries are still available.
     pub(crate) async fn load(&mut self, window: &web_sys::Window) -> Result<(), WasmError> {
         let dictionary_index = self.get_dictionary_index(window).await?;
 
-        let (word, sentence) = dictionary_index.construct_dictionaries(window).await?;
+        let (word, sentence, failures) = dictionary_index.construct_dictionaries(window).await;
 
         self.word_dictionaries = word;
         self.sentence_dictionaries = sentence;
+        self.load_failures = failures;
+
+        self.restore_user_dictionaries(storage::load_user_dictionary_records(window));
+
+        Ok(())
+    }
+
+    /// Registers user defined dictionaries constructed from persisted records.
+    /// When the records cannot be loaded, the failure is recorded instead.
+    fn restore_user_dictionaries(&mut self, records: Result<Vec<UserDictionaryRecord>, WasmError>) {
+        let records = match records {
+            Ok(records) => records,
+            Err(err) => {
+                self.user_dictionary_restore_error.replace(err.message());
+                return;
+            }
+        };
+        self.user_dictionary_restore_error = None;
+
+        for record in records.iter() {
+            self.dictionaries_mut(record.dictionary_type()).insert(
+                (DictionaryOrigin::UserDefined, record.name().to_string()),
+                record.construct_dictionary(),
+            );
+        }
+
+        self.user_dictionary_records = records;
+    }
+
+    /// Imports a user defined dictionary from its body text.
+    /// Dictionary with the same type and name is overwritten.
+    /// Updated records are saved by `save` first, so the library is unchanged when saving fails.
+    pub(crate) fn import_user_dictionary(
+        &mut self,
+        name: &str,
+        dictionary_type: DictionaryType,
+        body: &str,
+        save: impl FnOnce(&[UserDictionaryRecord]) -> Result<(), WasmError>,
+    ) -> Result<DictionaryInfo, WasmError> {
+        validate_dictionary_name(name)?;
+
+        let record = UserDictionaryRecord::new(name, dictionary_type, body);
+        let dictionary = record.construct_dictionary();
+        let dictionary_info = dictionary.construct_dictionary_info();
+
+        let mut records = self.user_dictionary_records.clone();
+        match self.find_user_dictionary_record(dictionary_type, name) {
+            Some(i) => records[i] = record,
+            None => records.push(record),
+        }
+        save(&records)?;
+
+        self.user_dictionary_records = records;
+        self.dictionaries_mut(dictionary_type).insert(
+            (DictionaryOrigin::UserDefined, name.to_string()),
+            dictionary,
+        );
+
+        Ok(dictionary_info)
+    }
+
+    /// Deletes a user defined dictionary.
+    /// Updated records are saved by `save` first, so the library is unchanged when saving fails.
+    pub(crate) fn delete_user_dictionary(
+        &mut self,
+        dictionary_type: DictionaryType,
+        name: &str,
+        save: impl FnOnce(&[UserDictionaryRecord]) -> Result<(), WasmError>,
+    ) -> Result<(), WasmError> {
+        let i = self
+            .find_user_dictionary_record(dictionary_type, name)
+            .ok_or(WasmError::new(WasmErrorKind::UserDictionaryNotFound(
+                name.to_string(),
+            )))?;
+
+        let mut records = self.user_dictionary_records.clone();
+        records.remove(i);
+        save(&records)?;
+
+        self.user_dictionary_records = records;
+        self.dictionaries_mut(dictionary_type)
+            .remove(&(DictionaryOrigin::UserDefined, name.to_string()));
 
         Ok(())
     }
 
+    /// Renames a user defined dictionary.
+    /// Renaming to the name of other existing dictionary is not allowed.
+    /// Updated records are saved by `save` first, so the library is unchanged when saving fails.
+    pub(crate) fn rename_user_dictionary(
+        &mut self,
+        dictionary_type: DictionaryType,
+        name: &str,
+        new_name: &str,
+        save: impl FnOnce(&[UserDictionaryRecord]) -> Result<(), WasmError>,
+    ) -> Result<DictionaryInfo, WasmError> {
+        validate_dictionary_name(new_name)?;
+
+        let i = self
+            .find_user_dictionary_record(dictionary_type, name)
+            .ok_or(WasmError::new(WasmErrorKind::UserDictionaryNotFound(
+                name.to_string(),
+            )))?;
+
+        if name != new_name
+            && self
+                .find_user_dictionary_record(dictionary_type, new_name)
+                .is_some()
+        {
+            return Err(WasmError::new(WasmErrorKind::DictionaryNameConflict(
+                new_name.to_string(),
+            )));
+        }
+
+        let mut records = self.user_dictionary_records.clone();
+        records[i].set_name(new_name);
+        let dictionary = records[i].construct_dictionary();
+        let dictionary_info = dictionary.construct_dictionary_info();
+        save(&records)?;
+
+        self.user_dictionary_records = records;
+
+        let dictionaries = self.dictionaries_mut(dictionary_type);
+        dictionaries.remove(&(DictionaryOrigin::UserDefined, name.to_string()));
+        dictionaries.insert(
+            (DictionaryOrigin::UserDefined, new_name.to_string()),
+            dictionary,
+        );
+
+        Ok(dictionary_info)
+    }
+
+    /// Returns index of the user defined dictionary record with the given type and name
+    fn find_user_dictionary_record(
+        &self,
+        dictionary_type: DictionaryType,
+        name: &str,
+    ) -> Option<usize> {
+        self.user_dictionary_records
+            .iter()
+            .position(|r| r.dictionary_type() == dictionary_type && r.name() == name)
+    }
+
+    /// Returns dictionaries of the given type
+    fn dictionaries(&self, dictionary_type: DictionaryType) -> &DictionariesInLibrary {
+        match dictionary_type {
+            DictionaryType::Word => &self.word_dictionaries,
+            DictionaryType::Sentence => &self.sentence_dictionaries,
+        }
+    }
+
+    /// Returns mutable dictionaries of the given type
+    fn dictionaries_mut(&mut self, dictionary_type: DictionaryType) -> &mut DictionariesInLibrary {
+        match dictionary_type {
+            DictionaryType::Word => &mut self.word_dictionaries,
+            DictionaryType::Sentence => &mut self.sentence_dictionaries,
+        }
+    }
+
     /// Loads the dictionary indeces from index JSON file
     async fn get_dictionary_index(
         &self,
@@ -101,31 +452,419 @@ impl Library {
                 .values()
                 .map(|d| d.construct_dictionary_info())
                 .collect(),
+            self.load_failures.clone(),
+            self.user_dictionary_restore_error.clone(),
         )
     }
 
     /// Constructs vocabulary entries for the given request.
+    /// Returns error when the request does not select any dictionary, selects a dictionary which
+    /// is not in the library, specifies invalid sentence range, or results in no vocabulary entry.
     pub(crate) fn construct_vocabulary_entries_for_request(
         &self,
         request: &QueryRequestFromUI,
-    ) -> Vec<&VocabularyEntry> {
+    ) -> Result<Vec<&VocabularyEntry>, WasmError> {
+        self.select_for_request(
+            request,
+            |dictionary| Ok(dictionary.get_vocabulary_entries()),
+        )
+    }
+
+    /// Constructs ideal key strokes of the vocabulary entries for the given request in the same
+    /// order as `construct_vocabulary_entries_for_request`.
+    /// Ideal key strokes are kept in each dictionary, so they are constructed only once.
+    pub(crate) fn construct_ideal_key_strokes_for_request(
+        &self,
+        request: &QueryRequestFromUI,
+    ) -> Result<Vec<&str>, WasmError> {
+        self.select_for_request(request, Dictionary::get_ideal_key_strokes)
+    }
+
+    /// Selects items of each vocabulary entry of the dictionaries for the given request.
+    /// Returns error in the same way as `construct_vocabulary_entries_for_request`.
+    fn select_for_request<'a, T>(
+        &'a self,
+        request: &QueryRequestFromUI,
+        items_of: impl Fn(&'a Dictionary) -> Result<Vec<T>, WasmError>,
+    ) -> Result<Vec<T>, WasmError> {
+        if request.used_dictionaries().is_empty() {
+            return Err(WasmError::new(WasmErrorKind::NoDictionarySelected));
+        }
+
         let mut vocabulary_entries = Vec::new();
 
         for (origin, name) in request.used_dictionaries() {
-            let dictionary = match request.dictionary_type() {
-                DictionaryType::Word => self
-                    .word_dictionaries
-                    .get(&(*origin, name.clone()))
-                    .unwrap(),
-                DictionaryType::Sentence => self
-                    .sentence_dictionaries
-                    .get(&(*origin, name.clone()))
-                    .unwrap(),
-            };
+            let dictionary = self
+                .dictionaries(request.dictionary_type())
+                .get(&(*origin, name.clone()))
+                .ok_or(WasmError::new(WasmErrorKind::UnknownDictionary(
+                    request.dictionary_type(),
+                    *origin,
+                    name.clone(),
+                )))?;
+
+            vocabulary_entries.append(&mut items_of(dictionary)?);
+        }
+
+        if let Some(sentence_range) = request.sentence_range() {
+            let index_range = match request.dictionary_type() {
+                DictionaryType::Sentence => sentence_range.to_index_range(vocabulary_entries.len()),
+                DictionaryType::Word => None,
+            }
+            .ok_or(WasmError::new(WasmErrorKind::InvalidSentenceRange(
+                sentence_range,
+                vocabulary_entries.len(),
+            )))?;
+
+            vocabulary_entries = vocabulary_entries.drain(index_range).collect();
+        }
+
+        if vocabulary_entries.is_empty() {
+            return Err(WasmError::new(WasmErrorKind::EmptyVocabulary));
+        }
+
+        Ok(vocabulary_entries)
+    }
+}
+
+#[cfg(test)]
+impl Library {
+    /// Registers a builtin dictionary from its body text, because builtin dictionaries cannot be
+    /// fetched from the server in tests
+    pub(crate) fn register_builtin_dictionary(
+        &mut self,
+        name: &str,
+        dictionary_type: DictionaryType,
+        body: &str,
+    ) {
+        self.dictionaries_mut(dictionary_type).insert(
+            (DictionaryOrigin::Builtin, name.to_string()),
+            Dictionary::parse(name, dictionary_type, DictionaryOrigin::Builtin, body, None),
+        );
+    }
+}
+
+#[wasm_bindgen(js_name = Library)]
+/// A handle of the library exported to JavaScript.
+/// Library is shared behind mutex because loading dictionaries is asynchronous.
+pub struct LibraryHandle {
+    library: Arc<Mutex<Library>>,
+}
 
-            vocabulary_entries.append(&mut dictionary.get_vocabulary_entries());
+#[wasm_bindgen(js_class = Library)]
+impl LibraryHandle {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            library: Arc::new(Mutex::new(Library::new())),
         }
+    }
+
+    /// Loads the dictionaries and returns the catalog of them
+    pub async fn get_dictionary_catalog(&self) -> Result<DictionaryCatalog, WasmError> {
+        let window = get_window()?;
+        let mut library = self.library.lock().await;
+
+        library.load(&window).await?;
+
+        Ok(library.construct_dictionary_catalog())
+    }
+
+    pub fn import_user_dictionary(
+        &self,
+        name: String,
+        dictionary_type: DictionaryType,
+        body: String,
+    ) -> Result<DictionaryInfo, WasmError> {
+        let window = get_window()?;
+        let mut library = self.blocking_lock();
+
+        library.import_user_dictionary(&name, dictionary_type, &body, |records| {
+            storage::save_user_dictionary_records(&window, records)
+        })
+    }
+
+    pub fn delete_user_dictionary(
+        &self,
+        dictionary_type: DictionaryType,
+        name: String,
+    ) -> Result<(), WasmError> {
+        let window = get_window()?;
+        let mut library = self.blocking_lock();
+
+        library.delete_user_dictionary(dictionary_type, &name, |records| {
+            storage::save_user_dictionary_records(&window, records)
+        })
+    }
+
+    pub fn rename_user_dictionary(
+        &self,
+        dictionary_type: DictionaryType,
+        name: String,
+        new_name: String,
+    ) -> Result<DictionaryInfo, WasmError> {
+        let window = get_window()?;
+        let mut library = self.blocking_lock();
+
+        library.rename_user_dictionary(dictionary_type, &name, &new_name, |records| {
+            storage::save_user_dictionary_records(&window, records)
+        })
+    }
+}
+
+impl LibraryHandle {
+    /// Locks the library synchronously
+    pub(crate) fn blocking_lock(&self) -> MutexGuard<'_, Library> {
+        self.library.blocking_lock()
+    }
+}
+
+impl Default for LibraryHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Validates the name of user defined dictionary
+/// Does nothing instead of saving user defined dictionary records to browser storage, which is
+/// not available in tests
+#[cfg(test)]
+pub(crate) fn skip_saving(_: &[UserDictionaryRecord]) -> Result<(), WasmError> {
+    Ok(())
+}
+
+fn validate_dictionary_name(name: &str) -> Result<(), WasmError> {
+    if name.trim().is_empty() {
+        return Err(WasmError::new(WasmErrorKind::InvalidDictionaryName(
+            name.to_string(),
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn import_user_dictionary_registers_dictionary_in_catalog() {
+        let mut library = Library::new();
+
+        let info = library
+            .import_user_dictionary(
+                "社内用語",
+                DictionaryType::Word,
+                "頑張る:がん,ば,る\n頑張る:が,ん,ば,る",
+                skip_saving,
+            )
+            .unwrap();
+
+        assert_eq!(info.origin, DictionaryOrigin::UserDefined);
+        assert_eq!(info.valid_vocabulary_count, 1);
+        assert_eq!(info.diagnostics.len(), 1);
+        assert_eq!(info.diagnostics[0].line_number, 2);
+
+        let catalog = library.construct_dictionary_catalog();
+        assert_eq!(catalog.word.len(), 1);
+        assert!(catalog.sentence.is_empty());
+    }
+
+    #[test]
+    fn rename_and_delete_user_dictionary_update_catalog() {
+        let mut library = Library::new();
+        library
+            .import_user_dictionary("a", DictionaryType::Word, "頑張る:がん,ば,る", skip_saving)
+            .unwrap();
+        library
+            .import_user_dictionary("b", DictionaryType::Word, "頑張る:がん,ば,る", skip_saving)
+            .unwrap();
+
+        assert!(library
+            .rename_user_dictionary(DictionaryType::Word, "a", "b", skip_saving)
+            .is_err());
+
+        let info = library
+            .rename_user_dictionary(DictionaryType::Word, "a", "c", skip_saving)
+            .unwrap();
+        assert_eq!(info.name, "c");
+        assert!(info.imported_at.is_some());
+
+        library
+            .delete_user_dictionary(DictionaryType::Word, "b", skip_saving)
+            .unwrap();
+        assert!(library
+            .delete_user_dictionary(DictionaryType::Word, "b", skip_saving)
+            .is_err());
+
+        let catalog = library.construct_dictionary_catalog();
+        assert_eq!(catalog.word.len(), 1);
+        assert_eq!(catalog.word[0].name, "c");
+        assert_eq!(library.user_dictionary_records.len(), 1);
+    }
+
+    #[test]
+    fn invalid_query_request_is_rejected() {
+        let mut library = Library::new();
+        library
+            .import_user_dictionary(
+                "valid",
+                DictionaryType::Word,
+                "頑張る:がん,ば,る",
+                skip_saving,
+            )
+            .unwrap();
+        library
+            .import_user_dictionary("invalid", DictionaryType::Word, "頑張る", skip_saving)
+            .unwrap();
+
+        let request = |used_dictionaries: &[&str]| {
+            QueryRequestFromUI::new(
+                DictionaryType::Word,
+                used_dictionaries
+                    .iter()
+                    .map(|name| (DictionaryOrigin::UserDefined, name.to_string()))
+                    .collect(),
+                None,
+            )
+        };
+
+        assert!(matches!(
+            library.construct_vocabulary_entries_for_request(&request(&[])),
+            Err(ref e) if matches!(e.kind(), WasmErrorKind::NoDictionarySelected)
+        ));
+        assert!(matches!(
+            library.construct_vocabulary_entries_for_request(&request(&["deleted"])),
+            Err(ref e) if matches!(e.kind(), WasmErrorKind::UnknownDictionary(..))
+        ));
+        assert!(matches!(
+            library.construct_vocabulary_entries_for_request(&request(&["invalid"])),
+            Err(ref e) if matches!(e.kind(), WasmErrorKind::EmptyVocabulary)
+        ));
+        assert_eq!(
+            library
+                .construct_vocabulary_entries_for_request(&request(&["valid", "invalid"]))
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn sentence_range_slices_vocabulary_entries() {
+        let mut library = Library::new();
+        library
+            .import_user_dictionary(
+                "long",
+                DictionaryType::Sentence,
+                "あ:あ\nい:い\nう:う\nえ:え\nお:お",
+                skip_saving,
+            )
+            .unwrap();
+
+        let request = |sentence_range: SentenceRange| {
+            QueryRequestFromUI::new(
+                DictionaryType::Sentence,
+                vec![(DictionaryOrigin::UserDefined, "long".to_string())],
+                None,
+            )
+            .with_sentence_range(sentence_range)
+        };
+        let n = |n: usize| NonZeroUsize::new(n).unwrap();
+        let views = |sentence_range: SentenceRange| {
+            library
+                .construct_vocabulary_entries_for_request(&request(sentence_range))
+                .map(|entries| {
+                    entries
+                        .iter()
+                        .map(|entry| entry.view().to_string())
+                        .collect::<Vec<String>>()
+                })
+        };
+
+        assert_eq!(
+            views(SentenceRange::Lines {
+                first: n(2),
+                last: n(4)
+            })
+            .unwrap(),
+            vec!["い", "う", "え"]
+        );
+        assert_eq!(
+            views(SentenceRange::From {
+                start: n(4),
+                count: n(2)
+            })
+            .unwrap(),
+            vec!["え", "お"]
+        );
+        assert!(matches!(
+            views(SentenceRange::From { start: n(4), count: n(3) }),
+            Err(ref e) if matches!(e.kind(), WasmErrorKind::InvalidSentenceRange(_, 5))
+        ));
+        assert!(matches!(
+            views(SentenceRange::Lines { first: n(3), last: n(2) }),
+            Err(ref e) if matches!(e.kind(), WasmErrorKind::InvalidSentenceRange(..))
+        ));
+        assert!(matches!(
+            views(SentenceRange::From { start: n(2), count: n(usize::MAX) }),
+            Err(ref e) if matches!(e.kind(), WasmErrorKind::InvalidSentenceRange(_, 5))
+        ));
+    }
+
+    #[test]
+    fn import_user_dictionary_rejects_empty_name() {
+        let mut library = Library::new();
+
+        assert!(library
+            .import_user_dictionary(" ", DictionaryType::Word, "頑張る:がん,ば,る", skip_saving)
+            .is_err());
+    }
+
+    #[test]
+    fn failure_of_restoring_user_dictionaries_is_reported_in_catalog() {
+        let mut library = Library::new();
+        library
+            .import_user_dictionary("a", DictionaryType::Word, "頑張る:がん,ば,る", skip_saving)
+            .unwrap();
+
+        library.restore_user_dictionaries(Err(WasmError::new(WasmErrorKind::CannotGetStorage)));
+
+        let catalog = library.construct_dictionary_catalog();
+        assert_eq!(catalog.word.len(), 1);
+        assert!(catalog.failed.is_empty());
+        assert!(catalog.user_dictionary_restore_error.is_some());
+
+        library.restore_user_dictionaries(Ok(vec![]));
+        assert!(library
+            .construct_dictionary_catalog()
+            .user_dictionary_restore_error
+            .is_none());
+    }
+
+    #[test]
+    fn failure_of_saving_user_dictionaries_leaves_library_unchanged() {
+        let mut library = Library::new();
+        library
+            .import_user_dictionary("a", DictionaryType::Word, "頑張る:がん,ば,る", skip_saving)
+            .unwrap();
+        let fail_saving =
+            |_: &[UserDictionaryRecord]| Err(WasmError::new(WasmErrorKind::CannotGetStorage));
+
+        assert!(library
+            .import_user_dictionary("b", DictionaryType::Word, "頑張る:がん,ば,る", fail_saving)
+            .is_err());
+        assert!(library
+            .rename_user_dictionary(DictionaryType::Word, "a", "c", fail_saving)
+            .is_err());
+        assert!(library
+            .delete_user_dictionary(DictionaryType::Word, "a", fail_saving)
+            .is_err());
 
-        vocabulary_entries
+        let catalog = library.construct_dictionary_catalog();
+        assert_eq!(catalog.word.len(), 1);
+        assert_eq!(catalog.word[0].name, "a");
+        assert_eq!(library.user_dictionary_records.len(), 1);
+        assert_eq!(library.user_dictionary_records[0].name(), "a");
     }
 }
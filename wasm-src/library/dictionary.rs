@@ -1,41 +1,56 @@
+use std::cell::OnceCell;
 use std::collections::HashMap;
+use std::num::NonZeroUsize;
 
+use chrono::{DateTime, Utc};
+use diagnostic::DictionaryDiagnostic;
 use futures::future::join_all;
 use serde::{Deserialize, Serialize};
 use serde_wasm_bindgen::from_value;
 use tsify::Tsify;
-use typing_engine::VocabularyEntry;
+use typing_engine::{
+    LapRequest, QueryRequest, TypingEngine, VocabularyEntry, VocabularyOrder, VocabularyQuantifier,
+    VocabularySeparator,
+};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
 
-use crate::WasmError;
+use crate::error::{describe_js_value, WasmError};
 
 use super::DictionariesInLibrary;
 
-/// Parses the body of a dictionary file and returns vocabulary entries and invalid line numbers.
-/// Line numbers are 1-indexed.
-fn parse_dictionary_body(body: &str) -> (Vec<VocabularyEntry>, Vec<usize>) {
+pub(crate) mod diagnostic;
+
+/// Parses the body of a dictionary file and returns vocabulary entries and diagnostics of invalid
+/// lines.
+fn parse_dictionary_body(body: &str) -> (Vec<VocabularyEntry>, Vec<DictionaryDiagnostic>) {
     let mut vocabulary_entries = Vec::new();
-    // 1-indexed line numbers
-    let mut invalid_line_numbers = Vec::new();
+    let mut diagnostics = Vec::new();
 
     for (i, line) in body.lines().enumerate() {
+        // 1-indexed line number
+        let line_number = i + 1;
+
         // Empty lines should be considered as error lines
         if line.trim().is_empty() {
-            invalid_line_numbers.push(i + 1);
+            diagnostics.push(DictionaryDiagnostic::empty_line(line_number));
             continue;
         }
 
         // Parse the line using typing_engine's parse_vocabulary_entry
         match typing_engine::parse_vocabulary_entry(line) {
             Ok(entry) => vocabulary_entries.push(entry),
-            Err(_) => {
-                invalid_line_numbers.push(i + 1);
+            Err(err) => {
+                diagnostics.push(DictionaryDiagnostic::from_parse_error(
+                    line_number,
+                    line,
+                    &err,
+                ));
             }
         }
     }
 
-    (vocabulary_entries, invalid_line_numbers)
+    (vocabulary_entries, diagnostics)
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Tsify)]
@@ -53,28 +68,101 @@ impl DictionaryIndexEntry {
         window: &web_sys::Window,
         name: &str,
         dictionary_type: DictionaryType,
-    ) -> Result<Dictionary, WasmError> {
+    ) -> Result<Dictionary, DictionaryLoadFailure> {
+        let failure = |reason: DictionaryLoadFailureReason| DictionaryLoadFailure {
+            name: name.to_string(),
+            dictionary_type,
+            path: self.path.clone(),
+            reason,
+        };
+
         let request_promise = window.fetch_with_str(&self.path);
 
-        let response = JsFuture::from(request_promise).await?;
-        let response: web_sys::Response = response.dyn_into()?;
+        let response = JsFuture::from(request_promise)
+            .await
+            .map_err(|e| failure(DictionaryLoadFailureReason::network(e)))?;
+        let response: web_sys::Response = response
+            .dyn_into()
+            .map_err(|e| failure(DictionaryLoadFailureReason::network(e)))?;
+
+        if !response.ok() {
+            return Err(failure(DictionaryLoadFailureReason::HttpStatus {
+                status: response.status(),
+            }));
+        }
 
-        let text = JsFuture::from(response.text()?).await?;
+        let text_promise = response
+            .text()
+            .map_err(|e| failure(DictionaryLoadFailureReason::decode_error(e)))?;
+        let text = JsFuture::from(text_promise)
+            .await
+            .map_err(|e| failure(DictionaryLoadFailureReason::decode_error(e)))?;
 
-        let body: String = from_value(text)?;
+        let body: String = from_value(text).map_err(|e| {
+            failure(DictionaryLoadFailureReason::DecodeError {
+                message: e.to_string(),
+            })
+        })?;
 
-        let (vocabulary_entries, invalid_line_numbers) = parse_dictionary_body(&body);
+        // Binary files are decoded with replacement characters, so such body is not a dictionary
+        if body.contains(['\0', char::REPLACEMENT_CHARACTER]) {
+            return Err(failure(DictionaryLoadFailureReason::NonTextBody));
+        }
 
-        Ok(Dictionary {
-            name: name.to_string(),
+        Ok(Dictionary::parse(
+            name,
             dictionary_type,
-            origin: DictionaryOrigin::Builtin,
-            vocabulary_entries,
-            invalid_line_numbers,
-        })
+            DictionaryOrigin::Builtin,
+            &body,
+            None,
+        ))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Tsify, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(tag = "type", rename_all = "snake_case")]
+/// Represents the reason why a dictionary failed to load
+pub enum DictionaryLoadFailureReason {
+    /// Request failed before receiving response
+    Network { message: String },
+    /// Server responded with non successful HTTP status
+    HttpStatus { status: u16 },
+    /// Response body is not a text
+    NonTextBody,
+    /// Response body cannot be decoded as a text
+    DecodeError { message: String },
+}
+
+impl DictionaryLoadFailureReason {
+    fn network(value: JsValue) -> Self {
+        Self::Network {
+            message: describe_js_value(&value),
+        }
+    }
+
+    fn decode_error(value: JsValue) -> Self {
+        Self::DecodeError {
+            message: describe_js_value(&value),
+        }
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+/// Contains information about a dictionary which failed to load
+pub struct DictionaryLoadFailure {
+    /// Name of the dictionary
+    pub name: String,
+    /// Type of dictionary
+    pub dictionary_type: DictionaryType,
+    /// Path of the dictionary file in the server
+    pub path: String,
+    /// Reason of the failure
+    pub reason: DictionaryLoadFailureReason,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Tsify)]
 #[tsify(from_wasm_abi)]
 /// Represents the dictionary index for library
@@ -86,11 +174,17 @@ pub(crate) struct DictionaryIndex {
 }
 
 impl DictionaryIndex {
-    /// Construsts actual dictionaries from holing indices
+    /// Construsts actual dictionaries from holing indices.
+    /// Dictionaries failed to load are returned along with the reasons instead of making whole
+    /// construction fail.
     pub(crate) async fn construct_dictionaries(
         &self,
         window: &web_sys::Window,
-    ) -> Result<(DictionariesInLibrary, DictionariesInLibrary), WasmError> {
+    ) -> (
+        DictionariesInLibrary,
+        DictionariesInLibrary,
+        Vec<DictionaryLoadFailure>,
+    ) {
         // Constructing dictionaries is done in parallel
         let futures = self
             .word
@@ -104,18 +198,14 @@ impl DictionaryIndex {
             .collect::<Vec<_>>();
 
         // Wait for all futures to complete
-        type ConstructResult = Result<Dictionary, WasmError>;
-        let (dictionaries, errors): (Vec<ConstructResult>, Vec<ConstructResult>) =
+        type ConstructResult = Result<Dictionary, DictionaryLoadFailure>;
+        let (dictionaries, failures): (Vec<ConstructResult>, Vec<ConstructResult>) =
             join_all(futures)
                 .await
                 .into_iter()
                 .partition(|result| result.is_ok());
         let dictionaries: Vec<_> = dictionaries.into_iter().map(Result::unwrap).collect();
-        let errors: Vec<_> = errors.into_iter().map(Result::unwrap_err).collect();
-
-        errors.into_iter().for_each(|error| {
-            web_sys::console::log_1(&format!("Error: {:?}", error).into());
-        });
+        let failures: Vec<_> = failures.into_iter().map(Result::unwrap_err).collect();
 
         // Parsed dictionaries must be partitioned by their type
         let (word_dictionaries, sentence_dictionaries): (Vec<Dictionary>, Vec<Dictionary>) =
@@ -123,7 +213,7 @@ impl DictionaryIndex {
                 .into_iter()
                 .partition(|dictionary| dictionary.dictionary_type == DictionaryType::Word);
 
-        Ok((
+        (
             word_dictionaries
                 .into_iter()
                 .map(|d| ((d.origin, d.name.clone()), d))
@@ -132,7 +222,8 @@ impl DictionaryIndex {
                 .into_iter()
                 .map(|d| ((d.origin, d.name.clone()), d))
                 .collect::<HashMap<_, _>>(),
-        ))
+            failures,
+        )
     }
 }
 
@@ -169,10 +260,13 @@ pub struct DictionaryInfo {
     pub dictionary_type: DictionaryType,
     /// Origin of the dictionary
     pub origin: DictionaryOrigin,
-    /// List of line numbers that contain invalid entries in the source file
-    pub invalid_line_numbers: Vec<usize>,
+    /// Diagnostics of lines that contain invalid entries in the source file
+    pub diagnostics: Vec<DictionaryDiagnostic>,
     /// Count of valid vocabulary entries in the dictionary
     pub valid_vocabulary_count: usize,
+    /// Date when the dictionary is imported in RFC 3339 format.
+    /// This is only available for user defined dictionaries.
+    pub imported_at: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -182,17 +276,45 @@ pub(crate) struct Dictionary {
     dictionary_type: DictionaryType,
     origin: DictionaryOrigin,
     vocabulary_entries: Vec<VocabularyEntry>,
-    invalid_line_numbers: Vec<usize>,
+    /// Ideal key strokes of each vocabulary entry.
+    /// Constructing them takes time for large dictionaries, so they are constructed only when
+    /// needed for the first time and kept afterwards.
+    ideal_key_strokes: OnceCell<Vec<String>>,
+    diagnostics: Vec<DictionaryDiagnostic>,
+    imported_at: Option<DateTime<Utc>>,
 }
+
 impl Dictionary {
+    /// Constructs dictionary by parsing the body of a dictionary file
+    pub(crate) fn parse(
+        name: &str,
+        dictionary_type: DictionaryType,
+        origin: DictionaryOrigin,
+        body: &str,
+        imported_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        let (vocabulary_entries, diagnostics) = parse_dictionary_body(body);
+
+        Self {
+            name: name.to_string(),
+            dictionary_type,
+            origin,
+            vocabulary_entries,
+            ideal_key_strokes: OnceCell::new(),
+            diagnostics,
+            imported_at,
+        }
+    }
+
     /// Returns the dictionary infomation that only contains metadata
     pub(crate) fn construct_dictionary_info(&self) -> DictionaryInfo {
         DictionaryInfo {
             name: self.name.clone(),
             dictionary_type: self.dictionary_type,
             origin: self.origin,
-            invalid_line_numbers: self.invalid_line_numbers.clone(),
+            diagnostics: self.diagnostics.clone(),
             valid_vocabulary_count: self.vocabulary_entries.len(),
+            imported_at: self.imported_at.map(|d| d.to_rfc3339()),
         }
     }
 
@@ -200,20 +322,70 @@ impl Dictionary {
     pub(crate) fn get_vocabulary_entries(&self) -> Vec<&VocabularyEntry> {
         self.vocabulary_entries.iter().collect()
     }
+
+    /// Returns ideal key strokes of the vocabulary entries in the same order
+    pub(crate) fn get_ideal_key_strokes(&self) -> Result<Vec<&str>, WasmError> {
+        let ideal_key_strokes = match self.ideal_key_strokes.get() {
+            Some(ideal_key_strokes) => ideal_key_strokes,
+            None => {
+                let ideal_key_strokes = self
+                    .vocabulary_entries
+                    .iter()
+                    .map(construct_ideal_key_strokes)
+                    .collect::<Result<Vec<String>, WasmError>>()?;
+
+                self.ideal_key_strokes.get_or_init(|| ideal_key_strokes)
+            }
+        };
+
+        Ok(ideal_key_strokes.iter().map(String::as_str).collect())
+    }
+}
+
+/// Constructs ideal key strokes of the vocabulary by typing engine
+fn construct_ideal_key_strokes(vocabulary_entry: &VocabularyEntry) -> Result<String, WasmError> {
+    let mut typing_engine = TypingEngine::new();
+    typing_engine.init(QueryRequest::new(
+        &[vocabulary_entry],
+        VocabularyQuantifier::Vocabulary(NonZeroUsize::new(1).unwrap()),
+        VocabularySeparator::None,
+        VocabularyOrder::InOrder,
+    ));
+    typing_engine.start()?;
+
+    let display_info = typing_engine
+        .construct_display_info(LapRequest::IdealKeyStroke(NonZeroUsize::new(50).unwrap()))?;
+
+    Ok(display_info.key_stroke_info().key_stroke().to_string())
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Tsify)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
 /// A dictionary metadata catalog that organizes dictionaries by their type
 pub struct DictionaryCatalog {
     /// Collection of worddictionaries
     pub word: Vec<DictionaryInfo>,
     /// Collection of sentencedictionaries
     pub sentence: Vec<DictionaryInfo>,
+    /// Collection of dictionaries failed to load
+    pub failed: Vec<DictionaryLoadFailure>,
+    /// Message of the error when user defined dictionaries cannot be restored from browser storage
+    pub user_dictionary_restore_error: Option<String>,
 }
 
 impl DictionaryCatalog {
-    pub(crate) fn new(word: Vec<DictionaryInfo>, sentence: Vec<DictionaryInfo>) -> Self {
-        Self { word, sentence }
+    pub(crate) fn new(
+        word: Vec<DictionaryInfo>,
+        sentence: Vec<DictionaryInfo>,
+        failed: Vec<DictionaryLoadFailure>,
+        user_dictionary_restore_error: Option<String>,
+    ) -> Self {
+        Self {
+            word,
+            sentence,
+            failed,
+            user_dictionary_restore_error,
+        }
     }
 }
@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use typing_engine::{SpellString, VocabularyParseError};
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Tsify, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "snake_case")]
+/// Represents the reason why a line of dictionary is rejected
+pub enum DictionaryDiagnosticKind {
+    /// Line is empty or only contains whitespaces
+    EmptyLine,
+    /// Line does not have colon separating the word and its readings
+    MissingColon,
+    /// Line has more than one non-escaped colon
+    TooManyColons,
+    /// Square brackets grouping characters are not balanced or nested
+    BracketMismatch,
+    /// Square brackets grouping characters enclose nothing
+    EmptyBracket,
+    /// Count of readings does not match count of characters in the word
+    SpellCountMismatch,
+    /// Reading contains characters other than hiragana, ASCII and japanese symbols
+    NonKanaReading,
+    /// Other reason
+    Other,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Tsify, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+/// Represents the diagnostic of a rejected line of dictionary
+pub struct DictionaryDiagnostic {
+    /// 1-indexed line number of the rejected line
+    pub line_number: usize,
+    /// 1-indexed column (in characters) where the offending segment starts if it is known
+    pub column: Option<usize>,
+    /// Offending segment of the line if it is known
+    pub segment: Option<String>,
+    /// Kind of the reason of rejection
+    pub kind: DictionaryDiagnosticKind,
+    /// Human readable message describing the reason of rejection
+    pub message: String,
+}
+
+impl DictionaryDiagnostic {
+    /// Constructs diagnostic for an empty line
+    pub(crate) fn empty_line(line_number: usize) -> Self {
+        Self {
+            line_number,
+            column: None,
+            segment: None,
+            kind: DictionaryDiagnosticKind::EmptyLine,
+            message: "Line is empty".to_string(),
+        }
+    }
+
+    /// Constructs diagnostic for a line rejected by the vocabulary parser.
+    /// Because the parser only reports its error as a message, the kind is inferred from the
+    /// structure of the line and the message.
+    pub(crate) fn from_parse_error(
+        line_number: usize,
+        line: &str,
+        error: &VocabularyParseError,
+    ) -> Self {
+        let colon_columns = non_escaped_positions(line, ':');
+
+        if colon_columns.is_empty() {
+            return Self {
+                line_number,
+                column: None,
+                segment: Some(line.to_string()),
+                kind: DictionaryDiagnosticKind::MissingColon,
+                message: "Colon (:) separating the word and its readings is missing".to_string(),
+            };
+        }
+
+        if colon_columns.len() > 1 {
+            return Self {
+                line_number,
+                column: Some(colon_columns[1] + 1),
+                segment: Some(":".to_string()),
+                kind: DictionaryDiagnosticKind::TooManyColons,
+                message: format!(
+                    "Line has {} colons but only one is allowed. Escape colons in the word or readings with a backslash",
+                    colon_columns.len()
+                ),
+            };
+        }
+
+        let colon_column = colon_columns[0];
+        let word: String = line.chars().take(colon_column).collect();
+        let readings: String = line.chars().skip(colon_column + 1).collect();
+        let parse_message = error.to_string();
+
+        if parse_message.starts_with("Compound symbol mismatch") {
+            Self {
+                line_number,
+                column: Some(1),
+                segment: Some(word),
+                kind: DictionaryDiagnosticKind::BracketMismatch,
+                message: "Square brackets in the word are not balanced or nested".to_string(),
+            }
+        } else if parse_message.starts_with("Empty compound") {
+            Self {
+                line_number,
+                column: Some(1),
+                segment: Some(word),
+                kind: DictionaryDiagnosticKind::EmptyBracket,
+                message: "Square brackets in the word enclose nothing".to_string(),
+            }
+        } else if parse_message.starts_with("View and spells count mismatch") {
+            Self {
+                line_number,
+                column: Some(colon_column + 2),
+                message: format!(
+                    "{} readings are given but it does not match the count of characters in `{}`",
+                    non_escaped_positions(&readings, ',').len() + 1,
+                    word
+                ),
+                segment: Some(readings),
+                kind: DictionaryDiagnosticKind::SpellCountMismatch,
+            }
+        } else if let Some(reading) = parse_message.strip_prefix("Invalid spell string: ") {
+            let message = match SpellString::try_from(reading.to_string()) {
+                Err(err) => format!("Reading `{}` is invalid: {}", reading, err),
+                Ok(_) => format!("Reading `{}` is invalid", reading),
+            };
+
+            Self {
+                line_number,
+                column: find_char_column(&readings, reading).map(|c| colon_column + 2 + c),
+                segment: Some(reading.to_string()),
+                kind: DictionaryDiagnosticKind::NonKanaReading,
+                message,
+            }
+        } else {
+            Self {
+                line_number,
+                column: None,
+                segment: None,
+                kind: DictionaryDiagnosticKind::Other,
+                message: parse_message,
+            }
+        }
+    }
+}
+
+/// Returns 0-indexed character positions of the separator which is not escaped by backslash
+fn non_escaped_positions(s: &str, separator: char) -> Vec<usize> {
+    let mut positions = Vec::new();
+    let mut is_prev_escape = false;
+
+    for (i, c) in s.chars().enumerate() {
+        if c == separator && !is_prev_escape {
+            positions.push(i);
+        }
+
+        is_prev_escape = c == '\\' && !is_prev_escape;
+    }
+
+    positions
+}
+
+/// Returns 0-indexed character position where the pattern first appears
+fn find_char_column(s: &str, pattern: &str) -> Option<usize> {
+    s.find(pattern).map(|byte_i| s[..byte_i].chars().count())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use typing_engine::parse_vocabulary_entry;
+
+    fn diagnose(line: &str) -> DictionaryDiagnostic {
+        let error = parse_vocabulary_entry(line).unwrap_err();
+        DictionaryDiagnostic::from_parse_error(1, line, &error)
+    }
+
+    #[test]
+    fn missing_colon_is_diagnosed() {
+        assert_eq!(
+            diagnose("頑張る").kind,
+            DictionaryDiagnosticKind::MissingColon
+        );
+    }
+
+    #[test]
+    fn escaped_colon_is_not_counted() {
+        let diagnostic = diagnose(r"a\:b:a:b");
+
+        assert_eq!(diagnostic.kind, DictionaryDiagnosticKind::TooManyColons);
+        assert_eq!(diagnostic.column, Some(7));
+    }
+
+    #[test]
+    fn spell_count_mismatch_is_diagnosed() {
+        let diagnostic = diagnose("頑張る:が,ん,ば,る");
+
+        assert_eq!(
+            diagnostic.kind,
+            DictionaryDiagnosticKind::SpellCountMismatch
+        );
+        assert_eq!(diagnostic.column, Some(5));
+        assert_eq!(diagnostic.segment.as_deref(), Some("が,ん,ば,る"));
+    }
+
+    #[test]
+    fn non_kana_reading_is_diagnosed_with_its_position() {
+        let diagnostic = diagnose("頑張る:ガン,ば,る");
+
+        assert_eq!(diagnostic.kind, DictionaryDiagnosticKind::NonKanaReading);
+        assert_eq!(diagnostic.column, Some(5));
+        assert_eq!(diagnostic.segment.as_deref(), Some("ガン"));
+    }
+
+    #[test]
+    fn bracket_mismatch_is_diagnosed() {
+        assert_eq!(
+            diagnose("[明日のジョー:あした,の,じ,ょ,ー").kind,
+            DictionaryDiagnosticKind::BracketMismatch
+        );
+    }
+}
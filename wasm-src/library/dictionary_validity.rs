@@ -16,10 +16,7 @@ fn parse_dictionary_body(filename: &str, body: &str) {
             Err(err) => {
                 panic!(
                     "File: {}. Line {}. about: {}, detail: {}",
-                    filename,
-                    line_number,
-                    err,
-                    err.to_string()
+                    filename, line_number, err, err
                 );
             }
         }
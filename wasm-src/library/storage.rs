@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::WasmError;
+use crate::utils::local_storage;
+
+use super::dictionary::{Dictionary, DictionaryOrigin, DictionaryType};
+
+/// Key of the browser storage entry holding user defined dictionaries
+const USER_DICTIONARY_STORAGE_KEY: &str = "typer-concierge-web.user-dictionaries";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+/// Represents a user defined dictionary persisted in browser storage.
+/// Raw body is persisted so that the dictionary can be reparsed when restoring.
+pub(crate) struct UserDictionaryRecord {
+    name: String,
+    dictionary_type: DictionaryType,
+    body: String,
+    imported_at: DateTime<Utc>,
+}
+
+impl UserDictionaryRecord {
+    pub(crate) fn new(name: &str, dictionary_type: DictionaryType, body: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            dictionary_type,
+            body: body.to_string(),
+            imported_at: Utc::now(),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    pub(crate) fn dictionary_type(&self) -> DictionaryType {
+        self.dictionary_type
+    }
+
+    /// Construct dictionary itself by parsing persisted body
+    pub(crate) fn construct_dictionary(&self) -> Dictionary {
+        Dictionary::parse(
+            &self.name,
+            self.dictionary_type,
+            DictionaryOrigin::UserDefined,
+            &self.body,
+            Some(self.imported_at),
+        )
+    }
+}
+
+/// Loads user defined dictionary records from browser storage.
+/// When nothing is persisted yet, empty records are returned.
+pub(crate) fn load_user_dictionary_records(
+    window: &web_sys::Window,
+) -> Result<Vec<UserDictionaryRecord>, WasmError> {
+    match local_storage(window)?.get_item(USER_DICTIONARY_STORAGE_KEY)? {
+        Some(json) => Ok(serde_json::from_str(&json)?),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Saves user defined dictionary records to browser storage
+pub(crate) fn save_user_dictionary_records(
+    window: &web_sys::Window,
+    records: &[UserDictionaryRecord],
+) -> Result<(), WasmError> {
+    let json = serde_json::to_string(records)?;
+
+    local_storage(window)?.set_item(USER_DICTIONARY_STORAGE_KEY, &json)?;
+
+    Ok(())
+}
@@ -1,9 +1,14 @@
 use serde::Serialize;
+use std::time::Duration;
 use tsify::Tsify;
-use typing_engine::EntitySkillStatistics;
+use typing_engine::DisplayInfo;
 use typing_engine::EntitySummaryStatistics;
-use typing_engine::KeyStrokeChar;
-use typing_engine::TypingResult as LibraryTypingResult;
+
+use crate::display::DisplayInformation;
+
+mod record;
+
+pub(crate) use record::{KeyStrokeSkillRecord, SummaryRecord, TypingResultRecord};
 
 #[derive(Debug, Clone, Serialize, Tsify)]
 #[serde(rename_all = "camelCase")]
@@ -13,20 +18,243 @@ use typing_engine::TypingResult as LibraryTypingResult;
 pub struct GameResult {
     this_result: TypingResult,
     aggregated_result: TypingResult,
+    /// Whether the current game is aborted before finishing.
+    /// Result of aborted game contains only the typed part.
+    is_aborted: bool,
+    /// Laps of the current game
+    laps: LapStatistics,
+    /// Message of the error when saving statistics to browser storage failed.
+    /// The game is finished or aborted regardless of it, so the result is still valid.
+    statistics_save_error: Option<String>,
+    /// Count of vocabularies missed in the current game.
+    /// Missed vocabularies can be retried only when this is not 0.
+    missed_vocabulary_count: usize,
 }
 
 impl GameResult {
     pub(crate) fn new(
-        this_result: LibraryTypingResult,
-        aggregated_result: LibraryTypingResult,
+        this_result: TypingResultRecord,
+        aggregated_result: TypingResultRecord,
+        is_aborted: bool,
+        laps: LapStatistics,
+        statistics_save_error: Option<String>,
+        missed_vocabulary_count: usize,
     ) -> Self {
         Self {
             this_result: this_result.into(),
             aggregated_result: aggregated_result.into(),
+            is_aborted,
+            laps,
+            statistics_save_error,
+            missed_vocabulary_count,
         }
     }
 }
 
+#[derive(Debug, Clone, Default, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi)]
+/// A struct representing statistics of each lap and consistency across laps
+pub struct LapStatistics {
+    laps: Vec<SingleLapStatistics>,
+    /// Standard deviation of KPM of complete laps, which is smaller when typing speed is more
+    /// consistent
+    kpm_standard_deviation: f64,
+    /// Index of the complete lap with the highest KPM
+    fastest_lap_index: Option<usize>,
+    /// Index of the complete lap with the lowest KPM
+    slowest_lap_index: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi)]
+/// A struct representing statistics of a single lap
+pub struct SingleLapStatistics {
+    duration_ms: u64,
+    key_stroke_count: usize,
+    /// Count of key strokes missed at least once
+    missed_count: usize,
+    /// Key strokes per minute
+    kpm: f64,
+    accuracy: f64,
+    /// Whether the lap reaches its lap end.
+    /// The typed part after the last lap end is an incomplete lap.
+    is_complete: bool,
+}
+
+impl LapStatistics {
+    /// Constructs statistics of laps in the typed part of display information.
+    /// The typed part after the last lap end is also counted as an incomplete lap because it is a
+    /// part of the game too, but it is not compared with the others because it is shorter.
+    pub(crate) fn new(display_information: &DisplayInformation, elapsed_time: Duration) -> Self {
+        let typed_count = display_information.current_cursor_position();
+        let elapsed_time_ms = elapsed_time.as_millis() as f64;
+
+        // Lap end time exists only for finished laps, and the last lap ends at the typed part
+        let mut lap_ends: Vec<(usize, f64, bool)> = display_information
+            .lap_end_positions()
+            .iter()
+            .map(|position| position + 1)
+            .zip(display_information.lap_end_time().iter().copied())
+            .map(|(end, end_time_ms)| (end, end_time_ms, true))
+            .collect();
+        let last_lap_end = lap_ends.last().map_or(0, |(end, _, _)| *end);
+        if typed_count > last_lap_end {
+            lap_ends.push((typed_count, elapsed_time_ms, false));
+        }
+
+        let mut laps = vec![];
+        let mut lap_start = (0, 0.0);
+        for (end, end_time_ms, is_complete) in lap_ends {
+            let (start, start_time_ms) = lap_start;
+            let missed_count = display_information
+                .missed_positions()
+                .iter()
+                .filter(|&&position| start <= position && position < end)
+                .count();
+
+            laps.push(SingleLapStatistics::new(
+                end - start,
+                missed_count,
+                end_time_ms - start_time_ms,
+                is_complete,
+            ));
+            lap_start = (end, end_time_ms);
+        }
+
+        Self::from_laps(laps)
+    }
+
+    fn from_laps(laps: Vec<SingleLapStatistics>) -> Self {
+        let complete_laps = || laps.iter().enumerate().filter(|(_, lap)| lap.is_complete);
+        let complete_lap_count = complete_laps().count();
+        if complete_lap_count == 0 {
+            return Self {
+                laps,
+                ..Self::default()
+            };
+        }
+
+        let mean_kpm =
+            complete_laps().map(|(_, lap)| lap.kpm).sum::<f64>() / complete_lap_count as f64;
+        let variance = complete_laps()
+            .map(|(_, lap)| (lap.kpm - mean_kpm).powi(2))
+            .sum::<f64>()
+            / complete_lap_count as f64;
+
+        let compare_kpm = |(_, a): &(usize, &SingleLapStatistics),
+                           (_, b): &(usize, &SingleLapStatistics)| {
+            a.kpm.total_cmp(&b.kpm)
+        };
+        let fastest_lap_index = complete_laps().max_by(compare_kpm).map(|(i, _)| i);
+        let slowest_lap_index = complete_laps().min_by(compare_kpm).map(|(i, _)| i);
+
+        Self {
+            laps,
+            kpm_standard_deviation: variance.sqrt(),
+            fastest_lap_index,
+            slowest_lap_index,
+        }
+    }
+}
+
+#[cfg(test)]
+impl GameResult {
+    pub(crate) fn missed_vocabulary_count(&self) -> usize {
+        self.missed_vocabulary_count
+    }
+}
+
+#[cfg(test)]
+impl LapStatistics {
+    pub(crate) fn laps(&self) -> &[SingleLapStatistics] {
+        &self.laps
+    }
+
+    pub(crate) fn fastest_lap_index(&self) -> Option<usize> {
+        self.fastest_lap_index
+    }
+
+    pub(crate) fn slowest_lap_index(&self) -> Option<usize> {
+        self.slowest_lap_index
+    }
+}
+
+impl SingleLapStatistics {
+    fn new(
+        key_stroke_count: usize,
+        missed_count: usize,
+        duration_ms: f64,
+        is_complete: bool,
+    ) -> Self {
+        let kpm = if duration_ms > 0.0 {
+            key_stroke_count as f64 * 60000.0 / duration_ms
+        } else {
+            0.0
+        };
+        let accuracy = if key_stroke_count == 0 {
+            0.0
+        } else {
+            (key_stroke_count - missed_count) as f64 / key_stroke_count as f64
+        };
+
+        Self {
+            duration_ms: duration_ms as u64,
+            key_stroke_count,
+            missed_count,
+            kpm,
+            accuracy,
+            is_complete,
+        }
+    }
+}
+
+#[cfg(test)]
+impl SingleLapStatistics {
+    pub(crate) fn key_stroke_count(&self) -> usize {
+        self.key_stroke_count
+    }
+
+    pub(crate) fn missed_count(&self) -> usize {
+        self.missed_count
+    }
+
+    pub(crate) fn is_complete(&self) -> bool {
+        self.is_complete
+    }
+}
+
+/// Constructs result for the typed part of a game which is not finished.
+///
+/// Typing engine can construct result only after the game is finished, so this result is composed
+/// of summary statistics in display information and skills observed by the session.
+/// Chunk summary is not available in display information, so it is left empty.
+pub(crate) fn construct_partial_result(
+    display_info: &DisplayInfo,
+    total_time: Duration,
+    single_key_stroke_skills: Vec<KeyStrokeSkillRecord>,
+) -> TypingResultRecord {
+    TypingResultRecord::new(
+        total_time,
+        typed_part_summary(display_info.key_stroke_info().summary_statistics()),
+        typed_part_summary(display_info.ideal_key_stroke_info().summary_statistics()),
+        typed_part_summary(display_info.spell_info().summary_statistics()),
+        SummaryRecord::default(),
+        single_key_stroke_skills,
+    )
+}
+
+/// Summary whose target is limited to the typed part so that speed is calculated correctly
+fn typed_part_summary(statistics: &EntitySummaryStatistics) -> SummaryRecord {
+    SummaryRecord::new(
+        statistics.finished_count(),
+        statistics.finished_count(),
+        statistics.completely_correct_count(),
+        statistics.wrong_count(),
+    )
+}
+
 #[derive(Debug, Clone, Serialize, Tsify)]
 #[serde(rename_all = "camelCase")]
 #[tsify(into_wasm_abi)]
@@ -38,17 +266,45 @@ pub struct TypingResult {
     single_key_stroke_skills: Vec<SingleKeyStrokeSkill>,
 }
 
-impl From<LibraryTypingResult> for TypingResult {
-    fn from(t: LibraryTypingResult) -> Self {
+impl TypingResult {
+    pub(crate) const CSV_HEADER: [&'static str; 7] = [
+        "total_time_ms",
+        "key_stroke_whole_count",
+        "key_stroke_completely_correct_count",
+        "key_stroke_missed_count",
+        "ideal_key_stroke_whole_count",
+        "ideal_key_stroke_completely_correct_count",
+        "ideal_key_stroke_missed_count",
+    ];
+
+    /// Returns fields of CSV row corresponding to `CSV_HEADER`
+    pub(crate) fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.total_time_ms.to_string(),
+            self.key_stroke.whole_count.to_string(),
+            self.key_stroke.completely_correct_count.to_string(),
+            self.key_stroke.missed_count.to_string(),
+            self.ideal_key_stroke.whole_count.to_string(),
+            self.ideal_key_stroke.completely_correct_count.to_string(),
+            self.ideal_key_stroke.missed_count.to_string(),
+        ]
+    }
+
+    pub(crate) fn single_key_stroke_skills(&self) -> &[SingleKeyStrokeSkill] {
+        &self.single_key_stroke_skills
+    }
+}
+
+impl From<TypingResultRecord> for TypingResult {
+    fn from(t: TypingResultRecord) -> Self {
         Self {
-            total_time_ms: t.total_time().as_millis().try_into().unwrap(),
-            key_stroke: t.summary().key_stroke().clone().into(),
-            ideal_key_stroke: t.summary().ideal_key_stroke().clone().into(),
+            total_time_ms: t.total_time_ms().try_into().unwrap(),
+            key_stroke: t.key_stroke().into(),
+            ideal_key_stroke: t.ideal_key_stroke().into(),
             single_key_stroke_skills: t
-                .skill_statistics()
-                .single_key_stroke()
+                .single_key_stroke_skills()
                 .iter()
-                .map(|s| SingleKeyStrokeSkill::from(s.clone()))
+                .map(SingleKeyStrokeSkill::from)
                 .collect(),
         }
     }
@@ -64,8 +320,8 @@ pub struct TypingResultTarget {
     missed_count: usize,
 }
 
-impl From<EntitySummaryStatistics> for TypingResultTarget {
-    fn from(t: EntitySummaryStatistics) -> Self {
+impl From<&SummaryRecord> for TypingResultTarget {
+    fn from(t: &SummaryRecord) -> Self {
         Self {
             whole_count: t.whole_count(),
             completely_correct_count: t.completely_correct_count(),
@@ -88,26 +344,75 @@ pub struct SingleKeyStrokeSkill {
     wrong_count_ranking: Vec<(String, usize)>,
 }
 
-impl From<EntitySkillStatistics<KeyStrokeChar>> for SingleKeyStrokeSkill {
-    fn from(t: EntitySkillStatistics<KeyStrokeChar>) -> Self {
-        let wrong_count = t
-            .wrong_count_ranking()
+impl SingleKeyStrokeSkill {
+    pub(crate) const CSV_HEADER: [&'static str; 6] = [
+        "key_stroke",
+        "count",
+        "wrong_count",
+        "completely_correct_count",
+        "average_time_ms",
+        "accuracy",
+    ];
+
+    pub(crate) const CONFUSION_CSV_HEADER: [&'static str; 3] =
+        ["expected_key_stroke", "wrong_key_stroke", "count"];
+
+    pub(crate) fn key_stroke(&self) -> &str {
+        &self.key_stroke
+    }
+
+    pub(crate) fn count(&self) -> usize {
+        self.count
+    }
+
+    pub(crate) fn average_time_ms(&self) -> u64 {
+        self.average_time_ms
+    }
+
+    pub(crate) fn accuracy(&self) -> f64 {
+        self.accuracy
+    }
+
+    /// Returns fields of CSV row corresponding to `CSV_HEADER`
+    pub(crate) fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.key_stroke.clone(),
+            self.count.to_string(),
+            self.wrong_count.to_string(),
+            self.completely_correct_count.to_string(),
+            self.average_time_ms.to_string(),
+            self.accuracy.to_string(),
+        ]
+    }
+
+    /// Returns CSV rows corresponding to `CONFUSION_CSV_HEADER`, one row for each wrong key stroke
+    pub(crate) fn confusion_csv_rows(&self) -> Vec<Vec<String>> {
+        self.wrong_count_ranking
             .iter()
-            .fold(0, |acc, (_, v)| acc + v);
+            .map(|(wrong_key_stroke, count)| {
+                vec![
+                    self.key_stroke.clone(),
+                    wrong_key_stroke.clone(),
+                    count.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+impl From<&KeyStrokeSkillRecord> for SingleKeyStrokeSkill {
+    fn from(t: &KeyStrokeSkillRecord) -> Self {
+        let wrong_count_ranking = t.wrong_count_ranking();
+        let wrong_count = wrong_count_ranking.iter().map(|(_, count)| count).sum();
 
         Self {
-            key_stroke: Into::<char>::into(t.entity().clone()).to_string(),
+            key_stroke: t.key_stroke().to_string(),
             count: t.count(),
             wrong_count,
             completely_correct_count: t.completely_correct_count(),
-            average_time_ms: t.average_time().as_secs() * 1000
-                + u64::from(t.average_time().subsec_millis()),
+            average_time_ms: t.average_time_ms(),
             accuracy: t.accuracy(),
-            wrong_count_ranking: t
-                .wrong_count_ranking()
-                .iter()
-                .map(|(k, v)| (Into::<char>::into(k.clone()).to_string(), *v))
-                .collect(),
+            wrong_count_ranking,
         }
     }
 }
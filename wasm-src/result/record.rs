@@ -0,0 +1,299 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::Add;
+use std::time::Duration;
+use typing_engine::{
+    EntitySkillStatistics, EntitySummaryStatistics, KeyStrokeChar,
+    TypingResult as LibraryTypingResult,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+/// Represents result of typing in the form owned by this crate.
+/// This is the form persisted in browser storage and exported, so that the schema does not depend
+/// on the private layout of typing engine.
+pub(crate) struct TypingResultRecord {
+    total_time_ms: u64,
+    key_stroke: SummaryRecord,
+    ideal_key_stroke: SummaryRecord,
+    spell: SummaryRecord,
+    chunk: SummaryRecord,
+    /// Skills of each key ordered by the key
+    single_key_stroke_skills: Vec<KeyStrokeSkillRecord>,
+}
+
+impl TypingResultRecord {
+    pub(crate) fn new(
+        total_time: Duration,
+        key_stroke: SummaryRecord,
+        ideal_key_stroke: SummaryRecord,
+        spell: SummaryRecord,
+        chunk: SummaryRecord,
+        single_key_stroke_skills: Vec<KeyStrokeSkillRecord>,
+    ) -> Self {
+        Self {
+            total_time_ms: duration_to_ms(total_time),
+            key_stroke,
+            ideal_key_stroke,
+            spell,
+            chunk,
+            single_key_stroke_skills,
+        }
+    }
+
+    pub(crate) fn total_time_ms(&self) -> u64 {
+        self.total_time_ms
+    }
+
+    pub(crate) fn key_stroke(&self) -> &SummaryRecord {
+        &self.key_stroke
+    }
+
+    pub(crate) fn ideal_key_stroke(&self) -> &SummaryRecord {
+        &self.ideal_key_stroke
+    }
+
+    pub(crate) fn single_key_stroke_skills(&self) -> &[KeyStrokeSkillRecord] {
+        &self.single_key_stroke_skills
+    }
+}
+
+#[cfg(test)]
+impl TypingResultRecord {
+    pub(crate) fn total_time(&self) -> Duration {
+        Duration::from_millis(self.total_time_ms)
+    }
+}
+
+impl From<LibraryTypingResult> for TypingResultRecord {
+    fn from(t: LibraryTypingResult) -> Self {
+        Self {
+            total_time_ms: duration_to_ms(t.total_time()),
+            key_stroke: t.summary().key_stroke().into(),
+            ideal_key_stroke: t.summary().ideal_key_stroke().into(),
+            spell: t.summary().spell().into(),
+            chunk: t.summary().chunk().into(),
+            single_key_stroke_skills: t
+                .skill_statistics()
+                .single_key_stroke()
+                .iter()
+                .map(KeyStrokeSkillRecord::from)
+                .collect(),
+        }
+    }
+}
+
+impl Add for TypingResultRecord {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut skills: BTreeMap<String, KeyStrokeSkillRecord> = BTreeMap::new();
+        for skill in self
+            .single_key_stroke_skills
+            .into_iter()
+            .chain(rhs.single_key_stroke_skills)
+        {
+            let skill = match skills.remove(&skill.key_stroke) {
+                Some(existing_skill) => existing_skill + skill,
+                None => skill,
+            };
+            skills.insert(skill.key_stroke.clone(), skill);
+        }
+
+        Self {
+            total_time_ms: self.total_time_ms + rhs.total_time_ms,
+            key_stroke: self.key_stroke + rhs.key_stroke,
+            ideal_key_stroke: self.ideal_key_stroke + rhs.ideal_key_stroke,
+            spell: self.spell + rhs.spell,
+            chunk: self.chunk + rhs.chunk,
+            single_key_stroke_skills: skills.into_values().collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+/// Represents counts of an entity (e.g. key stroke) of typing
+pub(crate) struct SummaryRecord {
+    whole_count: usize,
+    finished_count: usize,
+    completely_correct_count: usize,
+    wrong_count: usize,
+}
+
+impl SummaryRecord {
+    pub(crate) fn new(
+        whole_count: usize,
+        finished_count: usize,
+        completely_correct_count: usize,
+        wrong_count: usize,
+    ) -> Self {
+        Self {
+            whole_count,
+            finished_count,
+            completely_correct_count,
+            wrong_count,
+        }
+    }
+
+    pub(crate) fn whole_count(&self) -> usize {
+        self.whole_count
+    }
+
+    pub(crate) fn completely_correct_count(&self) -> usize {
+        self.completely_correct_count
+    }
+
+    pub(crate) fn wrong_count(&self) -> usize {
+        self.wrong_count
+    }
+}
+
+#[cfg(test)]
+impl SummaryRecord {
+    pub(crate) fn finished_count(&self) -> usize {
+        self.finished_count
+    }
+}
+
+impl From<&EntitySummaryStatistics> for SummaryRecord {
+    fn from(t: &EntitySummaryStatistics) -> Self {
+        Self::new(
+            t.whole_count(),
+            t.finished_count(),
+            t.completely_correct_count(),
+            t.wrong_count(),
+        )
+    }
+}
+
+impl Add for SummaryRecord {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.whole_count + rhs.whole_count,
+            self.finished_count + rhs.finished_count,
+            self.completely_correct_count + rhs.completely_correct_count,
+            self.wrong_count + rhs.wrong_count,
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+/// Represents skill of typing a single key
+pub(crate) struct KeyStrokeSkillRecord {
+    key_stroke: String,
+    count: usize,
+    cumulative_time_ms: u64,
+    completely_correct_count: usize,
+    /// Count of wrong key strokes typed instead of this key for each wrong key
+    wrong_counts: BTreeMap<String, usize>,
+}
+
+impl KeyStrokeSkillRecord {
+    /// Constructs skill of a single correct key stroke typed after the wrong key strokes
+    pub(crate) fn new(
+        key_stroke: &str,
+        required_time: Duration,
+        wrong_key_strokes: &[String],
+    ) -> Self {
+        let mut wrong_counts = BTreeMap::new();
+        for wrong_key_stroke in wrong_key_strokes {
+            *wrong_counts.entry(wrong_key_stroke.clone()).or_insert(0) += 1;
+        }
+
+        Self {
+            key_stroke: key_stroke.to_string(),
+            count: 1,
+            cumulative_time_ms: duration_to_ms(required_time),
+            completely_correct_count: usize::from(wrong_key_strokes.is_empty()),
+            wrong_counts,
+        }
+    }
+
+    pub(crate) fn key_stroke(&self) -> &str {
+        &self.key_stroke
+    }
+
+    pub(crate) fn count(&self) -> usize {
+        self.count
+    }
+
+    pub(crate) fn completely_correct_count(&self) -> usize {
+        self.completely_correct_count
+    }
+
+    pub(crate) fn average_time_ms(&self) -> u64 {
+        if self.count == 0 {
+            0
+        } else {
+            self.cumulative_time_ms / self.count as u64
+        }
+    }
+
+    pub(crate) fn accuracy(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.completely_correct_count as f64 / self.count as f64
+        }
+    }
+
+    /// Returns wrong key strokes and their counts from the most frequent one
+    pub(crate) fn wrong_count_ranking(&self) -> Vec<(String, usize)> {
+        let mut ranking: Vec<(String, usize)> = self
+            .wrong_counts
+            .iter()
+            .map(|(wrong_key_stroke, count)| (wrong_key_stroke.clone(), *count))
+            .collect();
+        ranking.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+        ranking
+    }
+}
+
+impl From<&EntitySkillStatistics<KeyStrokeChar>> for KeyStrokeSkillRecord {
+    fn from(t: &EntitySkillStatistics<KeyStrokeChar>) -> Self {
+        let key_stroke_string =
+            |key_stroke: &KeyStrokeChar| Into::<char>::into(key_stroke.clone()).to_string();
+
+        Self {
+            key_stroke: key_stroke_string(t.entity()),
+            count: t.count(),
+            // Cumulative time is not exposed, but average time in nanoseconds multiplied by count
+            // differs from it by less than a millisecond
+            cumulative_time_ms: duration_to_ms(t.average_time() * t.count() as u32),
+            completely_correct_count: t.completely_correct_count(),
+            wrong_counts: t
+                .wrong_count_ranking()
+                .iter()
+                .map(|(wrong_key_stroke, count)| (key_stroke_string(wrong_key_stroke), *count))
+                .collect(),
+        }
+    }
+}
+
+impl Add for KeyStrokeSkillRecord {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut wrong_counts = self.wrong_counts;
+        for (wrong_key_stroke, count) in rhs.wrong_counts {
+            *wrong_counts.entry(wrong_key_stroke).or_insert(0) += count;
+        }
+
+        Self {
+            key_stroke: self.key_stroke,
+            count: self.count + rhs.count,
+            cumulative_time_ms: self.cumulative_time_ms + rhs.cumulative_time_ms,
+            completely_correct_count: self.completely_correct_count + rhs.completely_correct_count,
+            wrong_counts,
+        }
+    }
+}
+
+fn duration_to_ms(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
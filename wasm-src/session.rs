@@ -0,0 +1,2044 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
+use std::rc::Rc;
+use std::time::Duration;
+use tsify::Tsify;
+use typing_engine::{
+    DisplayInfo, QueryRequest, TypingEngine, VocabularyEntry, VocabularyQuantifier,
+};
+use wasm_bindgen::prelude::*;
+
+mod bookmark;
+mod daily_challenge;
+mod history;
+mod key_stroke_skills;
+mod missed_vocabulary;
+mod recent_key_strokes;
+mod storage;
+mod weakness;
+
+use bookmark::{find_bookmark, remove_bookmark, rename_bookmark, update_bookmark, Bookmark};
+pub use daily_challenge::DailyChallengeHistory;
+use daily_challenge::{
+    construct_daily_challenge_history, construct_daily_challenge_request,
+    parse_daily_challenge_date, DailyChallengeRecord,
+};
+use history::{
+    construct_history_csv, construct_history_page, truncate_history, HistoryRecord, RemovedHistory,
+};
+pub use history::{HistoryPage, StatisticsImportSummary};
+use key_stroke_skills::KeyStrokeSkills;
+use missed_vocabulary::{recording_order, QueryVocabularies, VocabularyChooser};
+use recent_key_strokes::RecentKeyStrokes;
+use storage::PersistedStatistics;
+use weakness::{construct_weakness_scores, seeded_weighted_chooser};
+
+use crate::display::{DisplayInformation, LiveMetrics};
+use crate::error::{WasmError, WasmErrorKind};
+use crate::lap::LapPolicy;
+use crate::library::dictionary::{DictionaryOrigin, DictionaryType};
+use crate::library::{
+    Library, LibraryHandle, QueryOrder, QueryRequestFromUI, QuerySeparator, SentenceRange,
+};
+use crate::result::{
+    construct_partial_result, GameResult, LapStatistics, SingleKeyStrokeSkill, TypingResult,
+    TypingResultRecord,
+};
+use crate::utils::{construct_csv, get_window};
+
+/// Upper bound of typing speed used for generating query of time limited game
+const MAX_KEY_STROKES_PER_SECOND: usize = 20;
+
+/// Upper bound of time limit, which keeps query of time limited game small enough to generate
+const MAX_TIME_LIMIT_SEC: u32 = 600;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Tsify)]
+#[tsify(from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+/// A struct representing the information of a key stroke
+pub struct KeyStrokeInfo {
+    key: String,
+    elapsed_time_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+/// A struct repsenting the result of a key stroke
+pub struct StrokeKeyResult {
+    is_finished: bool,
+    display_information: DisplayInformation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "snake_case")]
+/// Represents the phase of game lifecycle.
+///
+/// Game proceeds as `Idle` -> `QueryConfirmed` -> `Running` -> `Finished`. A running game can be
+/// `Paused` and resumed, or `Aborted`. New query can be confirmed again from any phase other than
+/// `Running` and `Paused`.
+pub enum GamePhase {
+    /// No query is confirmed yet
+    Idle,
+    /// Query is confirmed and game is ready to start
+    QueryConfirmed,
+    /// Game is started and accepts key strokes
+    Running,
+    /// Game is paused and does not accept key strokes
+    Paused,
+    /// All of the query is typed
+    Finished,
+    /// Game is stopped before finishing
+    Aborted,
+}
+
+#[wasm_bindgen]
+/// A typing game session which owns its typing engine and aggregated result.
+/// Multiple sessions can exist at the same time, so each of them can be used for independent game.
+pub struct TypingSession {
+    phase: GamePhase,
+    typing_engine: TypingEngine,
+    /// Query request confirmed for current game
+    query_request: Option<QueryRequestFromUI>,
+    /// Time when current game is started
+    started_at: Option<DateTime<Utc>>,
+    /// Active elapsed time of the last key stroke in current game
+    elapsed_time: Duration,
+    /// Time when current pause is started
+    paused_at: Option<DateTime<Utc>>,
+    /// Total duration of pauses in current game, which is excluded from elapsed time
+    paused_duration: Duration,
+    /// Recent key strokes in current game for rolling speed
+    recent_key_strokes: RecentKeyStrokes,
+    /// Skill of each key in current game, which is used when game is not finished
+    key_stroke_skills: KeyStrokeSkills,
+    /// Result of the last finished or aborted game
+    this_result: Option<TypingResultRecord>,
+    /// Laps of the last finished or aborted game
+    this_laps: LapStatistics,
+    /// Message of the error when saving statistics of the last finished or aborted game failed
+    statistics_save_error: Option<String>,
+    aggregated_result: Option<TypingResultRecord>,
+    /// Finished games ordered from the oldest
+    history: Vec<HistoryRecord>,
+    /// Games removed from history but still counted in aggregated result, which must not be
+    /// imported again
+    removed_history: RemovedHistory,
+    /// Last completed sentences of sentence dictionaries
+    bookmarks: Vec<Bookmark>,
+    /// Bookmark to be updated when current game is finished
+    pending_bookmark: Option<Bookmark>,
+    /// How typing is divided into laps in display information and result
+    lap_policy: LapPolicy,
+    /// Vocabularies in query of current game
+    query_vocabularies: QueryVocabularies,
+    /// Vocabularies which had wrong key strokes in the last finished game
+    missed_vocabularies: Vec<VocabularyEntry>,
+    /// Date of daily challenge when current game is daily challenge
+    daily_challenge_date: Option<NaiveDate>,
+    /// Finished daily challenges ordered from the oldest, which are kept apart from normal games
+    daily_challenges: Vec<DailyChallengeRecord>,
+    /// Whether aggregated result is saved to browser storage whenever it changes
+    is_statistics_persistent: bool,
+}
+
+#[wasm_bindgen]
+impl TypingSession {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            phase: GamePhase::Idle,
+            typing_engine: TypingEngine::new(),
+            query_request: None,
+            started_at: None,
+            elapsed_time: Duration::ZERO,
+            paused_at: None,
+            paused_duration: Duration::ZERO,
+            recent_key_strokes: RecentKeyStrokes::new(),
+            key_stroke_skills: KeyStrokeSkills::default(),
+            this_result: None,
+            this_laps: LapStatistics::default(),
+            statistics_save_error: None,
+            aggregated_result: None,
+            history: Vec::new(),
+            removed_history: RemovedHistory::default(),
+            bookmarks: Vec::new(),
+            pending_bookmark: None,
+            lap_policy: LapPolicy::default(),
+            query_vocabularies: QueryVocabularies::default(),
+            missed_vocabularies: Vec::new(),
+            daily_challenge_date: None,
+            daily_challenges: Vec::new(),
+            is_statistics_persistent: false,
+        }
+    }
+
+    /// Returns current phase of game lifecycle
+    pub fn get_game_state(&self) -> GamePhase {
+        self.phase
+    }
+
+    pub fn get_lap_policy(&self) -> LapPolicy {
+        self.lap_policy
+    }
+
+    /// Changes how typing is divided into laps.
+    /// Laps of running game are fixed, so this is allowed only while no game is in progress.
+    pub fn set_lap_policy(&mut self, lap_policy: LapPolicy) -> Result<(), WasmError> {
+        self.ensure_phase(
+            &[
+                GamePhase::Idle,
+                GamePhase::QueryConfirmed,
+                GamePhase::Finished,
+                GamePhase::Aborted,
+            ],
+            "set_lap_policy",
+        )?;
+
+        self.lap_policy = lap_policy;
+
+        Ok(())
+    }
+
+    /// Constructs query from the dictionaries in the library and initializes the typing engine.
+    /// Returns the seed used for random order, which reproduces the same query when requested again.
+    pub fn confirm_query(
+        &mut self,
+        library: &LibraryHandle,
+        query_request: QueryRequestFromUI,
+    ) -> Result<Option<u32>, WasmError> {
+        let library = library.blocking_lock();
+
+        self.confirm_query_with_library(&library, &query_request)
+    }
+
+    /// Constructs query of daily challenge for the date in `YYYY-MM-DD` format.
+    /// The same query is constructed for the same date, and its result is recorded apart from
+    /// aggregated result and history of normal games.
+    pub fn confirm_daily_challenge(
+        &mut self,
+        library: &LibraryHandle,
+        date: String,
+    ) -> Result<(), WasmError> {
+        let library = library.blocking_lock();
+
+        self.confirm_daily_challenge_with_library(&library, &date)
+    }
+
+    /// Constructs query of the vocabularies which had wrong key strokes in the last finished game.
+    /// Each of them is typed once in the order they appeared, without time limit.
+    pub fn confirm_missed_vocabularies(&mut self) -> Result<(), WasmError> {
+        self.ensure_phase(&[GamePhase::Finished], "confirm_missed_vocabularies")?;
+
+        let (query_request, vocabulary_count) = self
+            .query_request
+            .as_ref()
+            .zip(NonZeroUsize::new(self.missed_vocabularies.len()))
+            .ok_or(WasmError::new(WasmErrorKind::NoMissedVocabulary))?;
+        let separator =
+            query_request
+                .separator()
+                .unwrap_or(match query_request.dictionary_type() {
+                    DictionaryType::Word => QuerySeparator::WhiteSpace,
+                    DictionaryType::Sentence => QuerySeparator::None,
+                });
+        // Missed vocabularies are recorded in history as a game of the same dictionaries
+        let query_request = QueryRequestFromUI::new(
+            query_request.dictionary_type(),
+            query_request.used_dictionaries().to_vec(),
+            None,
+        );
+
+        let missed_vocabularies = std::mem::take(&mut self.missed_vocabularies);
+        let vocabulary_entries: Vec<&VocabularyEntry> = missed_vocabularies.iter().collect();
+        let chosen_indices = Rc::new(RefCell::new(vec![]));
+        let request = QueryRequest::new(
+            &vocabulary_entries,
+            vocabulary_count_quantifier(vocabulary_count, separator),
+            separator.into(),
+            recording_order(in_order_chooser(), chosen_indices.clone()),
+        );
+
+        self.typing_engine.init(request);
+        self.query_vocabularies = QueryVocabularies::new(
+            chosen_indices
+                .borrow()
+                .iter()
+                .map(|&index| missed_vocabularies[index].clone())
+                .collect(),
+            separator,
+        );
+        self.phase = GamePhase::QueryConfirmed;
+        self.this_result.take();
+        self.this_laps = LapStatistics::default();
+        self.pending_bookmark = None;
+        self.daily_challenge_date = None;
+        self.query_request.replace(query_request);
+
+        Ok(())
+    }
+
+    pub fn start_game(&mut self) -> Result<DisplayInformation, WasmError> {
+        self.ensure_phase(&[GamePhase::QueryConfirmed], "start_game")?;
+
+        self.typing_engine.start()?;
+        self.phase = GamePhase::Running;
+        self.started_at.replace(Utc::now());
+        self.elapsed_time = Duration::ZERO;
+        self.paused_at = None;
+        self.paused_duration = Duration::ZERO;
+        self.recent_key_strokes = RecentKeyStrokes::new();
+        self.key_stroke_skills = KeyStrokeSkills::default();
+
+        let display_info = self
+            .typing_engine
+            .construct_display_info(self.lap_policy.lap_request())?;
+
+        Ok(self.construct_display_information(display_info))
+    }
+
+    pub fn stroke_key(
+        &mut self,
+        key_stroke_info: KeyStrokeInfo,
+    ) -> Result<StrokeKeyResult, WasmError> {
+        self.ensure_phase(&[GamePhase::Running], "stroke_key")?;
+
+        let elapsed_time_duration = self.active_elapsed_time(key_stroke_info.elapsed_time_ms);
+
+        // Key stroke after the time limit is not counted
+        if self.is_time_over(elapsed_time_duration) {
+            let display_information = self.finish_by_time_limit()?;
+
+            return Ok(StrokeKeyResult {
+                is_finished: true,
+                display_information,
+            });
+        }
+
+        if key_stroke_info.key.chars().count() != 1 {
+            return Err(WasmError::new(WasmErrorKind::StrokedKeyInvalid(
+                key_stroke_info.key,
+            )));
+        }
+        let key_stroke_char = key_stroke_info.key.chars().next().unwrap();
+
+        let is_finished = self.typing_engine.stroke_key_with_elapsed_time(
+            key_stroke_char.try_into().map_err(|_| {
+                WasmError::new(WasmErrorKind::StrokedKeyInvalid(
+                    key_stroke_info.key.clone(),
+                ))
+            })?,
+            elapsed_time_duration,
+        )?;
+        self.elapsed_time = elapsed_time_duration;
+
+        let display_info = self
+            .typing_engine
+            .construct_display_info(self.lap_policy.lap_request())?;
+        self.recent_key_strokes.record(
+            display_info
+                .key_stroke_info()
+                .summary_statistics()
+                .finished_count(),
+            elapsed_time_duration,
+        );
+        self.key_stroke_skills.record(
+            key_stroke_char,
+            display_info.key_stroke_info().summary_statistics(),
+            elapsed_time_duration,
+        );
+
+        let display_information = if is_finished {
+            let this_result = self
+                .typing_engine
+                .construct_result(self.lap_policy.lap_request())?;
+
+            self.finish_game(this_result.into(), display_info)?
+        } else {
+            self.construct_display_information(display_info)
+        };
+
+        Ok(StrokeKeyResult {
+            is_finished,
+            display_information,
+        })
+    }
+
+    /// Finishes the game when the time limit is passed without key strokes.
+    /// Returns whether the game is finished.
+    pub fn check_time_limit(&mut self, elapsed_time_ms: u64) -> Result<bool, WasmError> {
+        self.ensure_phase(&[GamePhase::Running], "check_time_limit")?;
+
+        if !self.is_time_over(self.active_elapsed_time(elapsed_time_ms)) {
+            return Ok(false);
+        }
+
+        self.finish_by_time_limit()?;
+
+        Ok(true)
+    }
+
+    pub fn get_result(&self) -> Result<GameResult, WasmError> {
+        self.ensure_phase(&[GamePhase::Finished, GamePhase::Aborted], "get_result")?;
+
+        let this_result =
+            self.this_result
+                .clone()
+                .ok_or(WasmError::new(WasmErrorKind::InternalError(
+                    "Result of this game is not constructed".to_string(),
+                )))?;
+
+        // Aggregated result can be empty when only aborted games are played without merging
+        let aggregated_result = self.aggregated_result.clone().unwrap_or_default();
+        let is_aborted = self.phase == GamePhase::Aborted;
+        // Only vocabularies of finished game can be retried
+        let missed_vocabulary_count = if is_aborted {
+            0
+        } else {
+            self.missed_vocabularies.len()
+        };
+
+        Ok(GameResult::new(
+            this_result,
+            aggregated_result,
+            is_aborted,
+            self.this_laps.clone(),
+            self.statistics_save_error.clone(),
+            missed_vocabulary_count,
+        ))
+    }
+
+    /// Pauses running game. Time until resuming is not counted as typing time.
+    pub fn pause_game(&mut self) -> Result<(), WasmError> {
+        self.pause_game_at(Utc::now())
+    }
+
+    /// Resumes paused game
+    pub fn resume_game(&mut self) -> Result<(), WasmError> {
+        self.resume_game_at(Utc::now())
+    }
+
+    /// Stops running game and returns result of the typed part.
+    /// When `merge_into_aggregated` is true, the partial result is also added to aggregated result
+    /// unless the game is daily challenge.
+    pub fn abort_game(&mut self, merge_into_aggregated: bool) -> Result<GameResult, WasmError> {
+        self.ensure_phase(&[GamePhase::Running, GamePhase::Paused], "abort_game")?;
+
+        let display_info = self
+            .typing_engine
+            .construct_display_info(self.lap_policy.lap_request())?;
+        let this_result = construct_partial_result(
+            &display_info,
+            self.elapsed_time,
+            self.key_stroke_skills.construct_skills(),
+        );
+        let display_information = self.construct_display_information(display_info);
+
+        self.phase = GamePhase::Aborted;
+        self.this_laps = LapStatistics::new(&display_information, self.elapsed_time);
+        if merge_into_aggregated && self.daily_challenge_date.is_none() {
+            self.merge_into_aggregated_result(&this_result);
+            self.statistics_save_error = self.on_statistics_changed().err().map(|e| e.message());
+        } else {
+            self.statistics_save_error = None;
+        }
+        self.this_result.replace(this_result);
+
+        self.get_result()
+    }
+
+    /// Resets aggregated result.
+    /// Games played before resetting are still remembered, so they are not counted again by
+    /// importing in the same way as games removed from history.
+    pub fn reset_statistics(&mut self) -> Result<(), WasmError> {
+        self.aggregated_result.take();
+
+        self.on_statistics_changed()
+    }
+
+    /// Returns all of the history from the newest game
+    pub fn get_history(&self) -> HistoryPage {
+        construct_history_page(&self.history, 0, self.history.len())
+    }
+
+    /// Returns `limit` entries of history from `offset`-th newest game
+    pub fn get_history_page(&self, offset: usize, limit: usize) -> HistoryPage {
+        construct_history_page(&self.history, offset, limit)
+    }
+
+    /// Deletes an entry of history.
+    /// Aggregated result is not changed because it does not keep results of each game, so the game
+    /// is remembered not to be counted again by importing.
+    pub fn delete_history_entry(&mut self, id: String) -> Result<(), WasmError> {
+        let index = self
+            .history
+            .iter()
+            .position(|record| record.id() == id)
+            .ok_or(WasmError::new(WasmErrorKind::HistoryEntryNotFound(id)))?;
+
+        let record = self.history.remove(index);
+        self.removed_history.remember(&[record]);
+
+        self.on_statistics_changed()
+    }
+
+    /// Moves bookmark of the renamed user defined dictionary to its new name
+    pub(crate) fn rename_user_dictionary_bookmark(
+        &mut self,
+        dictionary_type: DictionaryType,
+        name: &str,
+        new_name: &str,
+    ) -> Result<(), WasmError> {
+        // Only sentence dictionaries have bookmarks
+        if dictionary_type != DictionaryType::Sentence {
+            return Ok(());
+        }
+
+        let origin = DictionaryOrigin::UserDefined;
+        rename_bookmark(&mut self.bookmarks, origin, name, new_name);
+        rename_bookmark(self.pending_bookmark.as_mut_slice(), origin, name, new_name);
+
+        self.on_statistics_changed()
+    }
+
+    /// Removes bookmark of the deleted or overwritten user defined dictionary, because its
+    /// sentences are not the bookmarked ones anymore
+    pub(crate) fn remove_user_dictionary_bookmark(
+        &mut self,
+        dictionary_type: DictionaryType,
+        name: &str,
+    ) -> Result<(), WasmError> {
+        // Only sentence dictionaries have bookmarks
+        if dictionary_type != DictionaryType::Sentence {
+            return Ok(());
+        }
+
+        let origin = DictionaryOrigin::UserDefined;
+        remove_bookmark(&mut self.bookmarks, origin, name);
+        self.pending_bookmark
+            .take_if(|pending_bookmark| pending_bookmark.is_for(origin, name));
+
+        self.on_statistics_changed()
+    }
+
+    /// Returns number of the last completed sentence of the sentence dictionary
+    pub fn get_bookmark(&self, dictionary_origin: DictionaryOrigin, name: String) -> Option<usize> {
+        find_bookmark(&self.bookmarks, dictionary_origin, &name)
+            .map(|bookmark| bookmark.last_completed_sentence().get())
+    }
+
+    /// Returns all of the finished daily challenges from the newest
+    pub fn get_daily_challenge_history(&self) -> DailyChallengeHistory {
+        construct_daily_challenge_history(&self.daily_challenges)
+    }
+
+    /// Exports aggregated result and history as versioned JSON without losing any information
+    pub fn export_statistics_json(&self) -> Result<String, WasmError> {
+        self.persisted_statistics().to_json()
+    }
+
+    /// Exports history as CSV with one row for each game
+    pub fn export_history_csv(&self) -> String {
+        construct_history_csv(&self.history)
+    }
+
+    /// Exports aggregated skill statistics as CSV with one row for each key
+    pub fn export_key_stroke_skills_csv(&self) -> String {
+        let rows: Vec<Vec<String>> = self
+            .aggregated_key_stroke_skills()
+            .iter()
+            .map(SingleKeyStrokeSkill::csv_fields)
+            .collect();
+
+        construct_csv(&SingleKeyStrokeSkill::CSV_HEADER, &rows)
+    }
+
+    /// Exports aggregated wrong key strokes as CSV with one row for each pair of expected and wrong key
+    pub fn export_confusion_table_csv(&self) -> String {
+        let rows: Vec<Vec<String>> = self
+            .aggregated_key_stroke_skills()
+            .iter()
+            .flat_map(SingleKeyStrokeSkill::confusion_csv_rows)
+            .collect();
+
+        construct_csv(&SingleKeyStrokeSkill::CONFUSION_CSV_HEADER, &rows)
+    }
+
+    /// Imports statistics exported by `export_statistics_json`, possibly on other device.
+    ///
+    /// Only games not in history yet and not removed from history are added to history and
+    /// aggregated result, so importing the same file multiple times does not count games twice.
+    /// Aggregated result of games not kept in history (e.g. aborted games) cannot be deduplicated,
+    /// so it is not imported.
+    pub fn import_statistics_json(
+        &mut self,
+        json: String,
+    ) -> Result<StatisticsImportSummary, WasmError> {
+        let (_, imported_history, _, _, _) = PersistedStatistics::from_json(&json)?.into_parts();
+
+        let history_ids: HashSet<String> = self
+            .history
+            .iter()
+            .map(|record| record.id().to_string())
+            .collect();
+        let mut imported_count = 0;
+        let mut duplicated_count = 0;
+        for record in imported_history {
+            if history_ids.contains(record.id()) || self.removed_history.contains(&record) {
+                duplicated_count += 1;
+                continue;
+            }
+
+            self.merge_into_aggregated_result(record.result());
+            self.history.push(record);
+            imported_count += 1;
+        }
+
+        // Keep history ordered from the oldest even when games on multiple devices are mixed
+        self.history.sort_by_key(HistoryRecord::started_at);
+        let dropped_records = truncate_history(&mut self.history);
+        self.removed_history.remember_dropped(&dropped_records);
+
+        if imported_count != 0 {
+            self.on_statistics_changed()?;
+        }
+
+        Ok(StatisticsImportSummary::new(
+            imported_count,
+            duplicated_count,
+        ))
+    }
+
+    /// Loads aggregated result and history from browser storage.
+    /// After loading, they are saved automatically whenever they change.
+    pub fn load_statistics(&mut self) -> Result<(), WasmError> {
+        let window = get_window()?;
+
+        if let Some(statistics) = storage::load_statistics(&window)? {
+            (
+                self.aggregated_result,
+                self.history,
+                self.bookmarks,
+                self.daily_challenges,
+                self.removed_history,
+            ) = statistics.into_parts();
+        }
+        self.is_statistics_persistent = true;
+
+        Ok(())
+    }
+
+    /// Saves aggregated result and history to browser storage
+    pub fn save_statistics(&self) -> Result<(), WasmError> {
+        let window = get_window()?;
+
+        storage::save_statistics(&window, &self.persisted_statistics())
+    }
+}
+
+impl TypingSession {
+    /// Constructs query from the dictionaries in the library and initializes the typing engine.
+    /// Returns the seed used for random order.
+    pub(crate) fn confirm_query_with_library(
+        &mut self,
+        library: &Library,
+        query_request: &QueryRequestFromUI,
+    ) -> Result<Option<u32>, WasmError> {
+        self.ensure_phase(
+            &[
+                GamePhase::Idle,
+                GamePhase::QueryConfirmed,
+                GamePhase::Finished,
+                GamePhase::Aborted,
+            ],
+            "confirm_query",
+        )?;
+
+        if let Some(time_limit) = query_request.time_limit() {
+            let time_limit_sec = u32::try_from(time_limit.as_secs()).unwrap_or(u32::MAX);
+            if time_limit_sec > MAX_TIME_LIMIT_SEC {
+                return Err(WasmError::new(WasmErrorKind::InvalidTimeLimit(
+                    time_limit_sec,
+                    MAX_TIME_LIMIT_SEC,
+                )));
+            }
+        }
+
+        let query_request = &self.apply_bookmark(library, query_request)?;
+
+        let vocabulary_entries = library.construct_vocabulary_entries_for_request(query_request)?;
+        let vocabulary_count = NonZeroUsize::new(vocabulary_entries.len())
+            .ok_or(WasmError::new(WasmErrorKind::EmptyVocabulary))?;
+        self.pending_bookmark = construct_pending_bookmark(query_request, vocabulary_count);
+
+        // Time limited game needs query long enough that nobody can type it all in the time
+        let time_limited_quantifier = query_request.time_limit().map(|time_limit| {
+            let key_stroke_count = time_limit.as_secs() as usize * MAX_KEY_STROKES_PER_SECOND;
+            VocabularyQuantifier::KeyStroke(NonZeroUsize::new(key_stroke_count).unwrap())
+        });
+
+        let (quantifier, default_separator, default_order) = match query_request.dictionary_type() {
+            DictionaryType::Word => (
+                VocabularyQuantifier::KeyStroke(
+                    query_request
+                        .key_stroke_count_threshold()
+                        .unwrap_or(NonZeroUsize::new(150).unwrap()),
+                ),
+                QuerySeparator::WhiteSpace,
+                QueryOrder::Random,
+            ),
+            DictionaryType::Sentence => (
+                VocabularyQuantifier::Vocabulary(
+                    query_request
+                        .key_stroke_count_threshold()
+                        .unwrap_or(vocabulary_count),
+                ),
+                QuerySeparator::None,
+                QueryOrder::InOrder,
+            ),
+        };
+
+        let separator = query_request.separator().unwrap_or(default_separator);
+        let quantifier = match quantifier {
+            VocabularyQuantifier::Vocabulary(count) => {
+                vocabulary_count_quantifier(count, separator)
+            }
+            quantifier => quantifier,
+        };
+
+        // Seed is fixed here so that it is recorded with the request
+        let (chooser, query_request) = match query_request.order().unwrap_or(default_order) {
+            QueryOrder::Random => {
+                let seed = query_request.seed().unwrap_or_else(rand::random);
+                (seeded_random_chooser(seed), query_request.with_seed(seed))
+            }
+            QueryOrder::Weakness => {
+                let seed = query_request.seed().unwrap_or_else(rand::random);
+                let scores = construct_weakness_scores(
+                    &library.construct_ideal_key_strokes_for_request(query_request)?,
+                    &self.aggregated_key_stroke_skills(),
+                );
+                (
+                    seeded_weighted_chooser(seed, scores),
+                    query_request.with_seed(seed),
+                )
+            }
+            QueryOrder::InOrder => (in_order_chooser(), query_request.clone()),
+        };
+
+        // Chosen vocabularies are recorded to find vocabularies missed in the game
+        let chosen_indices = Rc::new(RefCell::new(vec![]));
+        let request = QueryRequest::new(
+            &vocabulary_entries,
+            time_limited_quantifier.unwrap_or(quantifier),
+            separator.into(),
+            recording_order(chooser, chosen_indices.clone()),
+        );
+
+        self.typing_engine.init(request);
+        self.query_vocabularies = QueryVocabularies::new(
+            chosen_indices
+                .borrow()
+                .iter()
+                .map(|&index| vocabulary_entries[index].clone())
+                .collect(),
+            separator,
+        );
+        self.missed_vocabularies.clear();
+        self.phase = GamePhase::QueryConfirmed;
+        self.this_result.take();
+        self.this_laps = LapStatistics::default();
+
+        self.daily_challenge_date = None;
+
+        let seed = query_request.seed();
+        self.query_request.replace(query_request);
+
+        Ok(seed)
+    }
+
+    pub(crate) fn confirm_daily_challenge_with_library(
+        &mut self,
+        library: &Library,
+        date: &str,
+    ) -> Result<(), WasmError> {
+        let date = parse_daily_challenge_date(date)?;
+
+        self.confirm_query_with_library(library, &construct_daily_challenge_request(date))?;
+        self.daily_challenge_date.replace(date);
+
+        Ok(())
+    }
+
+    fn pause_game_at(&mut self, now: DateTime<Utc>) -> Result<(), WasmError> {
+        self.ensure_phase(&[GamePhase::Running], "pause_game")?;
+
+        self.phase = GamePhase::Paused;
+        self.paused_at.replace(now);
+
+        Ok(())
+    }
+
+    fn resume_game_at(&mut self, now: DateTime<Utc>) -> Result<(), WasmError> {
+        self.ensure_phase(&[GamePhase::Paused], "resume_game")?;
+
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_duration += (now - paused_at).to_std().unwrap_or_default();
+        }
+        self.phase = GamePhase::Running;
+
+        Ok(())
+    }
+
+    /// Returns request continued from the bookmark when it is requested
+    fn apply_bookmark(
+        &self,
+        library: &Library,
+        query_request: &QueryRequestFromUI,
+    ) -> Result<QueryRequestFromUI, WasmError> {
+        if !query_request.continue_from_bookmark() {
+            return Ok(query_request.clone());
+        }
+
+        let (origin, name) = match (
+            query_request.dictionary_type(),
+            query_request.used_dictionaries(),
+        ) {
+            (DictionaryType::Sentence, [(origin, name)]) => (*origin, name),
+            _ => return Err(WasmError::new(WasmErrorKind::CannotContinueFromBookmark)),
+        };
+
+        let sentence_count = library
+            .construct_vocabulary_entries_for_request(&query_request.without_sentence_range())?
+            .len();
+
+        // Start from the first sentence again when whole dictionary is completed
+        let start = match find_bookmark(&self.bookmarks, origin, name) {
+            Some(bookmark) if bookmark.last_completed_sentence().get() < sentence_count => {
+                bookmark.last_completed_sentence().get() + 1
+            }
+            _ => 1,
+        };
+        let rest = sentence_count - start + 1;
+        let count = query_request
+            .key_stroke_count_threshold()
+            .map_or(rest, |threshold| threshold.get().min(rest));
+
+        Ok(query_request.with_sentence_range(SentenceRange::From {
+            start: NonZeroUsize::new(start).unwrap(),
+            count: NonZeroUsize::new(count).unwrap(),
+        }))
+    }
+
+    /// Converts elapsed time given from JavaScript, which includes pauses, into active typing time
+    fn active_elapsed_time(&self, elapsed_time_ms: u64) -> Duration {
+        Duration::from_millis(elapsed_time_ms).saturating_sub(self.paused_duration)
+    }
+
+    fn time_limit(&self) -> Option<Duration> {
+        self.query_request
+            .as_ref()
+            .and_then(QueryRequestFromUI::time_limit)
+    }
+
+    fn is_time_over(&self, elapsed_time: Duration) -> bool {
+        self.time_limit()
+            .is_some_and(|time_limit| elapsed_time >= time_limit)
+    }
+
+    /// Finishes time limited game with the typed part.
+    /// Total time of the result is the time limit itself so that speed is normalized over it.
+    fn finish_by_time_limit(&mut self) -> Result<DisplayInformation, WasmError> {
+        let time_limit = self.time_limit().unwrap_or(self.elapsed_time);
+
+        let display_info = self
+            .typing_engine
+            .construct_display_info(self.lap_policy.lap_request())?;
+        let this_result = construct_partial_result(
+            &display_info,
+            time_limit,
+            self.key_stroke_skills.construct_skills(),
+        );
+
+        self.elapsed_time = time_limit;
+
+        self.finish_game(this_result, display_info)
+    }
+
+    /// Converts display information for UI following the lap policy, with live metrics so far
+    fn construct_display_information(&self, display_info: DisplayInfo) -> DisplayInformation {
+        let view_lap_end_positions = display_info.lap_info().view_lap_end_positions();
+        let live_metrics = LiveMetrics::new(
+            display_info.key_stroke_info().summary_statistics(),
+            self.elapsed_time,
+            self.recent_key_strokes.rolling_kpm(),
+            self.time_limit(),
+        );
+        let display_information =
+            DisplayInformation::from(display_info).with_live_metrics(live_metrics);
+
+        match self.lap_policy {
+            LapPolicy::Vocabulary => display_information
+                .retain_laps(&view_lap_end_positions, |position| {
+                    self.query_vocabularies.is_vocabulary_end(position)
+                }),
+            _ => display_information,
+        }
+    }
+
+    /// Finishes the game with its result and the last display information.
+    /// Returns the display information converted for UI.
+    fn finish_game(
+        &mut self,
+        this_result: TypingResultRecord,
+        display_info: DisplayInfo,
+    ) -> Result<DisplayInformation, WasmError> {
+        // Fallible work is done before changing anything, so that the game is not left half finished
+        let history_record = match self.daily_challenge_date {
+            Some(_) => None,
+            None => Some(self.construct_history_record(&this_result)?),
+        };
+
+        if let Some(bookmark) = self.pending_bookmark.take() {
+            update_bookmark(&mut self.bookmarks, bookmark);
+        }
+        self.missed_vocabularies = self
+            .query_vocabularies
+            .construct_missed_vocabularies(display_info.view_info().wrong_positions());
+        let display_information = self.construct_display_information(display_info);
+        self.this_laps = LapStatistics::new(&display_information, self.elapsed_time);
+
+        self.phase = GamePhase::Finished;
+        // History record is constructed only when the game is not daily challenge
+        if let Some(date) = self.daily_challenge_date {
+            self.daily_challenges
+                .push(DailyChallengeRecord::new(date, this_result.clone()));
+        }
+        if let Some(history_record) = history_record {
+            self.merge_into_aggregated_result(&this_result);
+            self.record_history(history_record);
+        }
+        self.this_result.replace(this_result);
+        // Game is finished even when saving fails, and the failure is reported with its result
+        self.statistics_save_error = self.on_statistics_changed().err().map(|e| e.message());
+
+        Ok(display_information)
+    }
+
+    /// Returns error when the action is not allowed in current phase
+    fn ensure_phase(&self, allowed: &[GamePhase], action: &'static str) -> Result<(), WasmError> {
+        if allowed.contains(&self.phase) {
+            Ok(())
+        } else {
+            Err(WasmError::new(WasmErrorKind::IllegalGameTransition(
+                self.phase, action,
+            )))
+        }
+    }
+
+    fn aggregated_key_stroke_skills(&self) -> Vec<SingleKeyStrokeSkill> {
+        self.aggregated_result
+            .clone()
+            .map(|aggregated_result| {
+                TypingResult::from(aggregated_result)
+                    .single_key_stroke_skills()
+                    .to_vec()
+            })
+            .unwrap_or_default()
+    }
+
+    fn persisted_statistics(&self) -> PersistedStatistics {
+        PersistedStatistics::new(
+            self.aggregated_result.clone(),
+            self.history.clone(),
+            self.bookmarks.clone(),
+            self.daily_challenges.clone(),
+            self.removed_history.clone(),
+        )
+    }
+
+    fn on_statistics_changed(&self) -> Result<(), WasmError> {
+        if self.is_statistics_persistent {
+            self.save_statistics()?;
+        }
+
+        Ok(())
+    }
+
+    /// Constructs history record of finished game
+    fn construct_history_record(
+        &self,
+        this_result: &TypingResultRecord,
+    ) -> Result<HistoryRecord, WasmError> {
+        let (query_request, started_at) =
+            self.query_request
+                .as_ref()
+                .zip(self.started_at)
+                .ok_or(WasmError::new(WasmErrorKind::InternalError(
+                    "Query of finished game is not recorded".to_string(),
+                )))?;
+
+        HistoryRecord::new(query_request, started_at, Utc::now(), this_result.clone())
+    }
+
+    /// Records finished game into history
+    fn record_history(&mut self, history_record: HistoryRecord) {
+        self.history.push(history_record);
+        let dropped_records = truncate_history(&mut self.history);
+        self.removed_history.remember_dropped(&dropped_records);
+    }
+
+    fn merge_into_aggregated_result(&mut self, this_result: &TypingResultRecord) {
+        let new_aggregated_result = if let Some(aggregated_result) = self.aggregated_result.take() {
+            aggregated_result + this_result.clone()
+        } else {
+            this_result.clone()
+        };
+
+        self.aggregated_result.replace(new_aggregated_result);
+    }
+}
+
+/// Returns chooser choosing vocabularies randomly, which is reproducible with the same seed
+fn seeded_random_chooser(seed: u32) -> VocabularyChooser {
+    let rng = RefCell::new(ChaCha8Rng::seed_from_u64(seed.into()));
+
+    Box::new(move |_, vocabulary_entries| rng.borrow_mut().gen_range(0..vocabulary_entries.len()))
+}
+
+/// Returns quantifier of the count of vocabularies.
+/// Typing engine counts inserted separators as vocabularies too, so they are added to the count.
+fn vocabulary_count_quantifier(
+    vocabulary_count: NonZeroUsize,
+    separator: QuerySeparator,
+) -> VocabularyQuantifier {
+    let count = match separator {
+        QuerySeparator::WhiteSpace => vocabulary_count.get() * 2 - 1,
+        QuerySeparator::None => vocabulary_count.get(),
+    };
+
+    VocabularyQuantifier::Vocabulary(NonZeroUsize::new(count).unwrap())
+}
+
+/// Returns chooser choosing vocabularies in order in the same way as `VocabularyOrder::InOrder`
+fn in_order_chooser() -> VocabularyChooser {
+    Box::new(|prev_index, vocabulary_entries| {
+        prev_index.map_or(0, |prev_index| (prev_index + 1) % vocabulary_entries.len())
+    })
+}
+
+/// Constructs bookmark which is updated when the game of the request is finished.
+/// Bookmark is only for sentences of single dictionary typed in order without time limit.
+fn construct_pending_bookmark(
+    query_request: &QueryRequestFromUI,
+    vocabulary_count: NonZeroUsize,
+) -> Option<Bookmark> {
+    let is_in_order = matches!(query_request.order(), None | Some(QueryOrder::InOrder));
+    if query_request.time_limit().is_some() || !is_in_order {
+        return None;
+    }
+
+    let (origin, name) = match (
+        query_request.dictionary_type(),
+        query_request.used_dictionaries(),
+    ) {
+        (DictionaryType::Sentence, [(origin, name)]) => (*origin, name),
+        _ => return None,
+    };
+
+    let first = query_request
+        .sentence_range()
+        .map_or(1, |sentence_range| sentence_range.first().get());
+    let typed_count = query_request
+        .key_stroke_count_threshold()
+        .map_or(vocabulary_count, |threshold| {
+            threshold.min(vocabulary_count)
+        });
+
+    Some(Bookmark::new(
+        origin,
+        name,
+        NonZeroUsize::new(first + typed_count.get() - 1).unwrap(),
+    ))
+}
+
+impl Default for TypingSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::daily_challenge::DAILY_CHALLENGE_DICTIONARY_NAME;
+    use super::history::MAX_HISTORY_LENGTH;
+    use super::*;
+    use crate::library::skip_saving;
+
+    /// Constructs a library containing a single sentence dictionary
+    fn construct_library() -> Library {
+        let mut library = Library::new();
+        library
+            .import_user_dictionary(
+                "test",
+                DictionaryType::Sentence,
+                "あい:あ,い\nうえ:う,え",
+                skip_saving,
+            )
+            .unwrap();
+
+        library
+    }
+
+    fn construct_query_request() -> QueryRequestFromUI {
+        serde_json::from_str(
+            r#"{"dictionaryType":"sentence","usedDictionaries":[["user_defined","test"]],"keyStrokeCountThreshold":null}"#,
+        )
+        .unwrap()
+    }
+
+    /// Word dictionary whose key strokes are "ka i ke" when typed in order
+    const WORD_DICTIONARY_BODY: &str = "か:か\nい:い\nけ:け";
+
+    fn construct_word_library() -> Library {
+        let mut library = Library::new();
+        library
+            .import_user_dictionary(
+                "words",
+                DictionaryType::Word,
+                WORD_DICTIONARY_BODY,
+                skip_saving,
+            )
+            .unwrap();
+
+        library
+    }
+
+    /// Query of typing the words in `construct_word_library()` once in order
+    fn construct_word_query_request() -> QueryRequestFromUI {
+        serde_json::from_str(
+            r#"{"dictionaryType":"word","usedDictionaries":[["user_defined","words"]],"keyStrokeCountThreshold":7,"order":"in_order"}"#,
+        )
+        .unwrap()
+    }
+
+    /// Strokes the expected keys until the game finishes
+    fn play_until_finished(session: &mut TypingSession) {
+        play_until_finished_from(session, 0);
+    }
+
+    /// Returns the key expected to be stroked next
+    fn expected_key(session: &TypingSession) -> char {
+        let display_info = session
+            .typing_engine
+            .construct_display_info(session.lap_policy.lap_request())
+            .unwrap();
+        let key_stroke_info = display_info.key_stroke_info();
+
+        key_stroke_info
+            .key_stroke()
+            .chars()
+            .nth(key_stroke_info.current_cursor_position())
+            .unwrap()
+    }
+
+    /// Strokes the expected keys every 100ms after `elapsed_time_ms` until the game finishes
+    fn play_until_finished_from(session: &mut TypingSession, mut elapsed_time_ms: u64) {
+        loop {
+            let key = expected_key(session);
+
+            elapsed_time_ms += 100;
+            let result = session
+                .stroke_key(KeyStrokeInfo {
+                    key: key.to_string(),
+                    elapsed_time_ms,
+                })
+                .unwrap();
+
+            if result.is_finished {
+                break;
+            }
+        }
+    }
+
+    /// Strokes the expected keys until the game finishes, striking wrong key before `missed_key`
+    fn play_until_finished_missing(session: &mut TypingSession, missed_key: char) {
+        let mut elapsed_time_ms = 0;
+        while session.phase == GamePhase::Running {
+            let key = expected_key(session);
+
+            let keys = if key == missed_key {
+                vec!['x', key]
+            } else {
+                vec![key]
+            };
+            for key in keys {
+                elapsed_time_ms += 100;
+                session
+                    .stroke_key(KeyStrokeInfo {
+                        key: key.to_string(),
+                        elapsed_time_ms,
+                    })
+                    .unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn sessions_are_independent_of_each_other() {
+        let library = construct_library();
+        let query_request = construct_query_request();
+
+        let mut finished_session = TypingSession::new();
+        finished_session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        finished_session.start_game().unwrap();
+        play_until_finished(&mut finished_session);
+
+        let mut idle_session = TypingSession::new();
+        idle_session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+
+        assert!(finished_session.get_result().is_ok());
+        assert!(idle_session.get_result().is_err());
+        assert_eq!(finished_session.get_game_state(), GamePhase::Finished);
+        assert_eq!(idle_session.get_game_state(), GamePhase::QueryConfirmed);
+
+        finished_session.reset_statistics().unwrap();
+        assert!(finished_session.aggregated_result.is_none());
+    }
+
+    #[test]
+    fn illegal_transitions_are_rejected() {
+        let library = construct_library();
+        let query_request = construct_query_request();
+        let mut session = TypingSession::new();
+
+        assert!(session.start_game().is_err());
+
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        assert!(session.get_result().is_err());
+        assert!(session
+            .stroke_key(KeyStrokeInfo {
+                key: "a".to_string(),
+                elapsed_time_ms: 0,
+            })
+            .is_err());
+
+        session.start_game().unwrap();
+        assert!(session.start_game().is_err());
+        assert!(session
+            .confirm_query_with_library(&library, &query_request)
+            .is_err());
+        assert_eq!(session.get_game_state(), GamePhase::Running);
+
+        play_until_finished(&mut session);
+        assert!(session
+            .stroke_key(KeyStrokeInfo {
+                key: "a".to_string(),
+                elapsed_time_ms: 0,
+            })
+            .is_err());
+
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        assert_eq!(session.get_game_state(), GamePhase::QueryConfirmed);
+    }
+
+    #[test]
+    fn aborted_game_returns_partial_result() {
+        let library = construct_library();
+        let query_request = construct_query_request();
+        let mut session = TypingSession::new();
+
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        session.start_game().unwrap();
+        session
+            .stroke_key(KeyStrokeInfo {
+                key: "a".to_string(),
+                elapsed_time_ms: 300,
+            })
+            .unwrap();
+
+        session.abort_game(false).unwrap();
+        let this_result = session.this_result.as_ref().unwrap();
+        assert_eq!(this_result.total_time(), Duration::from_millis(300));
+        assert_eq!(this_result.key_stroke().finished_count(), 1);
+        assert_eq!(session.get_game_state(), GamePhase::Aborted);
+        assert!(session.aggregated_result.is_none());
+        assert!(session
+            .stroke_key(KeyStrokeInfo {
+                key: "i".to_string(),
+                elapsed_time_ms: 400,
+            })
+            .is_err());
+
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        session.start_game().unwrap();
+        session.abort_game(true).unwrap();
+        assert!(session.aggregated_result.is_some());
+    }
+
+    #[test]
+    fn paused_time_is_excluded_from_elapsed_time() {
+        let library = construct_library();
+        let query_request = construct_query_request();
+        let mut session = TypingSession::new();
+
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        session.start_game().unwrap();
+
+        let paused_at = Utc::now();
+        session.pause_game_at(paused_at).unwrap();
+        assert_eq!(session.get_game_state(), GamePhase::Paused);
+        assert!(session
+            .stroke_key(KeyStrokeInfo {
+                key: "a".to_string(),
+                elapsed_time_ms: 100,
+            })
+            .is_err());
+
+        session
+            .resume_game_at(paused_at + chrono::Duration::milliseconds(1000))
+            .unwrap();
+        play_until_finished_from(&mut session, 1000);
+
+        // Keys are stroked every 100ms from 1100ms in JavaScript time, and there are 4 key strokes
+        let this_result = session.this_result.as_ref().unwrap();
+        assert_eq!(this_result.total_time(), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn persisted_statistics_restores_aggregated_result() {
+        let library = construct_library();
+        let query_request = construct_query_request();
+        let mut session = TypingSession::new();
+
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        session.start_game().unwrap();
+        play_until_finished(&mut session);
+
+        let json = session.persisted_statistics().to_json().unwrap();
+        let (aggregated_result, history, _, _, _) =
+            PersistedStatistics::from_json(&json).unwrap().into_parts();
+        assert_eq!(aggregated_result, session.aggregated_result);
+        assert_eq!(history, session.history);
+
+        // Statistics persisted before history is introduced can be loaded
+        let (_, history, _, _, _) =
+            PersistedStatistics::from_json(r#"{"version":1,"aggregatedResult":null}"#)
+                .unwrap()
+                .into_parts();
+        assert!(history.is_empty());
+
+        // Results in the serialized form of typing engine are migrated
+        let engine_result = session
+            .typing_engine
+            .construct_result(session.lap_policy.lap_request())
+            .unwrap();
+        let engine_result_json = serde_json::json!({
+            "version": 1,
+            "aggregatedResult": engine_result,
+            "history": [],
+        })
+        .to_string();
+        let (aggregated_result, _, _, _, _) = PersistedStatistics::from_json(&engine_result_json)
+            .unwrap()
+            .into_parts();
+        assert_eq!(aggregated_result, Some(engine_result.into()));
+        assert_eq!(aggregated_result, session.aggregated_result);
+
+        let unsupported = json.replace(r#""version":2"#, r#""version":999"#);
+        assert!(matches!(
+            PersistedStatistics::from_json(&unsupported),
+            Err(ref e) if matches!(e.kind(), WasmErrorKind::UnsupportedStatisticsVersion(999))
+        ));
+    }
+
+    #[test]
+    fn finished_games_are_recorded_in_history() {
+        let library = construct_library();
+        let query_request = construct_query_request();
+        let mut session = TypingSession::new();
+
+        for _ in 0..3 {
+            session
+                .confirm_query_with_library(&library, &query_request)
+                .unwrap();
+            session.start_game().unwrap();
+            play_until_finished(&mut session);
+        }
+
+        // Aborted game is not recorded
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        session.start_game().unwrap();
+        session.abort_game(true).unwrap();
+
+        assert_eq!(session.get_history().total_count(), 3);
+        assert_eq!(session.get_history_page(2, 10).entries().len(), 1);
+
+        let newest_id = session.get_history_page(0, 1).entries()[0].id().to_string();
+        assert_eq!(newest_id, session.history[2].id());
+
+        session.delete_history_entry(newest_id.clone()).unwrap();
+        assert_eq!(session.get_history().total_count(), 2);
+        assert!(session.delete_history_entry(newest_id).is_err());
+    }
+
+    #[test]
+    fn history_is_limited_to_the_newest_games() {
+        let library = construct_library();
+        let query_request = construct_query_request();
+        let mut session = TypingSession::new();
+
+        for _ in 0..2 {
+            session
+                .confirm_query_with_library(&library, &query_request)
+                .unwrap();
+            session.start_game().unwrap();
+            play_until_finished(&mut session);
+        }
+        let oldest_record = session.history[0].clone();
+        let aggregated_result = session.aggregated_result.clone();
+        session.history = vec![oldest_record.clone(); MAX_HISTORY_LENGTH - 1]
+            .into_iter()
+            .chain(session.history.drain(1..))
+            .collect();
+
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        session.start_game().unwrap();
+        play_until_finished(&mut session);
+
+        assert_eq!(session.get_history().total_count(), MAX_HISTORY_LENGTH);
+        assert_eq!(
+            session
+                .history
+                .iter()
+                .filter(|&record| *record == oldest_record)
+                .count(),
+            MAX_HISTORY_LENGTH - 2
+        );
+        assert!(session.removed_history.contains(&oldest_record));
+        // Dropped games are still counted in aggregated result
+        assert_eq!(
+            session.aggregated_result,
+            aggregated_result
+                .zip(session.this_result.clone())
+                .map(|(a, b)| a + b)
+        );
+    }
+
+    #[test]
+    fn game_is_not_finished_when_history_cannot_be_recorded() {
+        let library = construct_word_library();
+        let mut session = TypingSession::new();
+
+        session
+            .confirm_query_with_library(&library, &construct_word_query_request())
+            .unwrap();
+        session.start_game().unwrap();
+        // Start time is required to record history
+        session.started_at.take();
+
+        let mut elapsed_time_ms = 0;
+        let result = loop {
+            let key = expected_key(&session);
+
+            elapsed_time_ms += 100;
+            match session.stroke_key(KeyStrokeInfo {
+                key: key.to_string(),
+                elapsed_time_ms,
+            }) {
+                Ok(result) if !result.is_finished => continue,
+                result => break result,
+            }
+        };
+
+        assert!(matches!(
+            result,
+            Err(ref e) if matches!(e.kind(), WasmErrorKind::InternalError(_))
+        ));
+        assert_eq!(session.get_game_state(), GamePhase::Running);
+        assert!(session.this_result.is_none());
+        assert!(session.aggregated_result.is_none());
+        assert!(session.history.is_empty());
+    }
+
+    #[test]
+    fn statistics_are_exported_as_csv() {
+        let library = construct_library();
+        let query_request = construct_query_request();
+        let mut session = TypingSession::new();
+
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        session.start_game().unwrap();
+        // Wrong key stroke before playing to the end
+        session
+            .stroke_key(KeyStrokeInfo {
+                key: "x".to_string(),
+                elapsed_time_ms: 50,
+            })
+            .unwrap();
+        play_until_finished(&mut session);
+
+        let history_csv = session.export_history_csv();
+        let history_lines: Vec<&str> = history_csv.lines().collect();
+        assert_eq!(history_lines.len(), 2);
+        assert!(history_lines[0].starts_with("id,started_at,finished_at"));
+        assert!(history_lines[1].contains(",sentence,test,,"));
+
+        let skills_csv = session.export_key_stroke_skills_csv();
+        assert!(skills_csv.lines().any(|line| line.starts_with("a,1,1,0,")));
+
+        let confusion_csv = session.export_confusion_table_csv();
+        assert_eq!(
+            confusion_csv,
+            "expected_key_stroke,wrong_key_stroke,count\na,x,1\n"
+        );
+    }
+
+    #[test]
+    fn imported_statistics_are_deduplicated() {
+        let library = construct_library();
+        let query_request = construct_query_request();
+
+        let mut other_device_session = TypingSession::new();
+        for _ in 0..2 {
+            other_device_session
+                .confirm_query_with_library(&library, &query_request)
+                .unwrap();
+            other_device_session.start_game().unwrap();
+            play_until_finished(&mut other_device_session);
+        }
+        let json = other_device_session.export_statistics_json().unwrap();
+
+        let mut session = TypingSession::new();
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        session.start_game().unwrap();
+        play_until_finished(&mut session);
+
+        session.import_statistics_json(json.clone()).unwrap();
+        let aggregated_result = session.aggregated_result.clone();
+        assert_eq!(session.history.len(), 3);
+        assert_eq!(
+            aggregated_result.as_ref().unwrap().total_time(),
+            Duration::from_millis(1200)
+        );
+
+        // Importing the same file again changes nothing
+        session.import_statistics_json(json.clone()).unwrap();
+        assert_eq!(session.history.len(), 3);
+        assert_eq!(session.aggregated_result, aggregated_result);
+
+        // Deleted game is not imported again because it is still counted in aggregated result
+        let imported_id = other_device_session.history[0].id().to_string();
+        session.delete_history_entry(imported_id).unwrap();
+        session.import_statistics_json(json.clone()).unwrap();
+        assert_eq!(session.history.len(), 2);
+        assert_eq!(session.aggregated_result, aggregated_result);
+
+        // Games played before resetting are not counted again either
+        session.reset_statistics().unwrap();
+        let summary = session.import_statistics_json(json).unwrap();
+        assert_eq!(summary.imported_count(), 0);
+        assert!(session.aggregated_result.is_none());
+
+        assert!(session
+            .import_statistics_json(r#"{"version":999}"#.to_string())
+            .is_err());
+    }
+
+    #[test]
+    fn removed_history_is_bounded() {
+        let query_request = construct_query_request();
+        let now = Utc::now();
+        let record = |i: usize| {
+            let started_at = now + chrono::Duration::seconds(i as i64);
+            HistoryRecord::new(
+                &query_request,
+                started_at,
+                started_at,
+                TypingResultRecord::default(),
+            )
+            .unwrap()
+        };
+        let records: Vec<HistoryRecord> = (0..MAX_HISTORY_LENGTH * 2).map(record).collect();
+
+        let mut removed_history = RemovedHistory::default();
+        for record in records.iter() {
+            removed_history.remember(std::slice::from_ref(record));
+        }
+        assert_eq!(removed_history.entry_count(), MAX_HISTORY_LENGTH);
+        assert!(records.iter().all(|r| removed_history.contains(r)));
+        assert!(!removed_history.contains(&record(MAX_HISTORY_LENGTH * 2)));
+
+        // Dropped games are covered by their start time without keeping identifiers
+        removed_history.remember_dropped(&records);
+        assert_eq!(removed_history.entry_count(), 0);
+        assert!(records.iter().all(|r| removed_history.contains(r)));
+        assert!(!removed_history.contains(&record(MAX_HISTORY_LENGTH * 2)));
+    }
+
+    #[test]
+    fn time_limited_game_is_finished_when_time_passes() {
+        let library = construct_library();
+        let query_request: QueryRequestFromUI = serde_json::from_str(
+            r#"{"dictionaryType":"sentence","usedDictionaries":[["user_defined","test"]],"keyStrokeCountThreshold":null,"timeLimitSec":1}"#,
+        )
+        .unwrap();
+        let mut session = TypingSession::new();
+
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        session.start_game().unwrap();
+        session
+            .stroke_key(KeyStrokeInfo {
+                key: "x".to_string(),
+                elapsed_time_ms: 300,
+            })
+            .unwrap();
+        session
+            .stroke_key(KeyStrokeInfo {
+                key: "a".to_string(),
+                elapsed_time_ms: 500,
+            })
+            .unwrap();
+        assert!(!session.check_time_limit(900).unwrap());
+
+        let result = session
+            .stroke_key(KeyStrokeInfo {
+                key: "i".to_string(),
+                elapsed_time_ms: 1100,
+            })
+            .unwrap();
+        assert!(result.is_finished);
+        assert_eq!(session.get_game_state(), GamePhase::Finished);
+
+        // Key stroke after the time limit is not counted and speed is normalized over the limit
+        let this_result = session.this_result.as_ref().unwrap();
+        assert_eq!(this_result.total_time(), Duration::from_secs(1));
+        assert_eq!(this_result.key_stroke().whole_count(), 1);
+        assert_eq!(session.history.len(), 1);
+
+        // Skills of the typed part are kept although the game is not finished by typing
+        let skills = this_result.single_key_stroke_skills();
+        assert_eq!(skills.len(), 1);
+        assert_eq!(skills[0].key_stroke(), "a");
+        assert_eq!(skills[0].count(), 1);
+        assert_eq!(skills[0].completely_correct_count(), 0);
+        assert_eq!(skills[0].average_time_ms(), 500);
+        assert_eq!(skills[0].wrong_count_ranking(), vec![("x".to_string(), 1)]);
+    }
+
+    #[test]
+    fn too_long_time_limit_is_rejected() {
+        let library = construct_library();
+        let mut session = TypingSession::new();
+
+        for time_limit_sec in [MAX_TIME_LIMIT_SEC + 1, u32::MAX] {
+            let query_request: QueryRequestFromUI = serde_json::from_str(&format!(
+                r#"{{"dictionaryType":"sentence","usedDictionaries":[["user_defined","test"]],"keyStrokeCountThreshold":null,"timeLimitSec":{}}}"#,
+                time_limit_sec
+            ))
+            .unwrap();
+
+            assert!(matches!(
+                session.confirm_query_with_library(&library, &query_request),
+                Err(ref e) if matches!(e.kind(), WasmErrorKind::InvalidTimeLimit(..))
+            ));
+        }
+        assert_eq!(session.get_game_state(), GamePhase::Idle);
+    }
+
+    #[test]
+    fn order_and_separator_can_be_specified() {
+        let library = construct_library();
+        let mut session = TypingSession::new();
+
+        let query_request: QueryRequestFromUI = serde_json::from_str(
+            r#"{"dictionaryType":"sentence","usedDictionaries":[["user_defined","test"]],"keyStrokeCountThreshold":null,"separator":"white_space"}"#,
+        )
+        .unwrap();
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        session.start_game().unwrap();
+
+        let display_info = session
+            .typing_engine
+            .construct_display_info(session.lap_policy.lap_request())
+            .unwrap();
+        assert_eq!(display_info.view_info().view(), "あい うえ");
+    }
+
+    #[test]
+    fn sentences_are_continued_from_bookmark() {
+        let mut library = Library::new();
+        library
+            .import_user_dictionary(
+                "long",
+                DictionaryType::Sentence,
+                "あ:あ\nい:い\nう:う",
+                skip_saving,
+            )
+            .unwrap();
+        let query_request: QueryRequestFromUI = serde_json::from_str(
+            r#"{"dictionaryType":"sentence","usedDictionaries":[["user_defined","long"]],"keyStrokeCountThreshold":2,"continueFromBookmark":true}"#,
+        )
+        .unwrap();
+        let mut session = TypingSession::new();
+
+        let play = |session: &mut TypingSession| {
+            session
+                .confirm_query_with_library(&library, &query_request)
+                .unwrap();
+            session.start_game().unwrap();
+            let display_info = session
+                .typing_engine
+                .construct_display_info(session.lap_policy.lap_request())
+                .unwrap();
+            let view = display_info.view_info().view().to_string();
+            play_until_finished(session);
+
+            view
+        };
+
+        assert_eq!(play(&mut session), "あい");
+        assert_eq!(
+            session.get_bookmark(DictionaryOrigin::UserDefined, "long".to_string()),
+            Some(2)
+        );
+        assert_eq!(play(&mut session), "う");
+        assert_eq!(
+            session.get_bookmark(DictionaryOrigin::UserDefined, "long".to_string()),
+            Some(3)
+        );
+        // Whole dictionary is completed, so it starts from the first sentence again
+        assert_eq!(play(&mut session), "あい");
+
+        // Bookmark follows the dictionary when it is renamed, and is dropped when it is deleted
+        session
+            .rename_user_dictionary_bookmark(DictionaryType::Sentence, "long", "longer")
+            .unwrap();
+        assert_eq!(
+            session.get_bookmark(DictionaryOrigin::UserDefined, "long".to_string()),
+            None
+        );
+        assert_eq!(
+            session.get_bookmark(DictionaryOrigin::UserDefined, "longer".to_string()),
+            Some(2)
+        );
+        session
+            .remove_user_dictionary_bookmark(DictionaryType::Sentence, "longer")
+            .unwrap();
+        assert_eq!(
+            session.get_bookmark(DictionaryOrigin::UserDefined, "longer".to_string()),
+            None
+        );
+    }
+
+    #[test]
+    fn same_seed_reproduces_same_query() {
+        let mut library = Library::new();
+        library
+            .import_user_dictionary(
+                "words",
+                DictionaryType::Word,
+                "あ:あ\nい:い\nう:う\nえ:え\nお:お",
+                skip_saving,
+            )
+            .unwrap();
+        let query_request = |seed: &str| -> QueryRequestFromUI {
+            serde_json::from_str(&format!(
+                r#"{{"dictionaryType":"word","usedDictionaries":[["user_defined","words"]],"keyStrokeCountThreshold":50,"seed":{}}}"#,
+                seed
+            ))
+            .unwrap()
+        };
+        let view = |query_request: &QueryRequestFromUI| {
+            let mut session = TypingSession::new();
+            let seed = session
+                .confirm_query_with_library(&library, query_request)
+                .unwrap();
+            session.start_game().unwrap();
+            let display_info = session
+                .typing_engine
+                .construct_display_info(session.lap_policy.lap_request())
+                .unwrap();
+
+            (seed, display_info.view_info().view().to_string())
+        };
+
+        let (seed, first_view) = view(&query_request("42"));
+        assert_eq!(seed, Some(42));
+        assert_eq!(view(&query_request("42")).1, first_view);
+
+        // Seed is generated when not specified, and it reproduces the same query
+        let (generated_seed, generated_view) = view(&query_request("null"));
+        let generated_seed = generated_seed.unwrap();
+        assert_eq!(
+            view(&query_request(&generated_seed.to_string())).1,
+            generated_view
+        );
+    }
+
+    #[test]
+    fn daily_challenge_is_recorded_apart_from_normal_games() {
+        let first =
+            construct_daily_challenge_request(parse_daily_challenge_date("2024-05-01").unwrap());
+        let second =
+            construct_daily_challenge_request(parse_daily_challenge_date("2024-05-02").unwrap());
+        assert_eq!(
+            first,
+            construct_daily_challenge_request(parse_daily_challenge_date("2024-05-01").unwrap())
+        );
+        assert_ne!(first.seed(), second.seed());
+
+        let mut session = TypingSession::new();
+        assert!(matches!(
+            session.confirm_daily_challenge_with_library(&Library::new(), "2024/05/01"),
+            Err(ref e) if matches!(e.kind(), WasmErrorKind::InvalidDate(_))
+        ));
+
+        let mut library = construct_library();
+        library.register_builtin_dictionary(
+            DAILY_CHALLENGE_DICTIONARY_NAME,
+            DictionaryType::Word,
+            WORD_DICTIONARY_BODY,
+        );
+        session
+            .confirm_daily_challenge_with_library(&library, "2024-05-01")
+            .unwrap();
+        session.start_game().unwrap();
+        play_until_finished(&mut session);
+
+        assert!(session.aggregated_result.is_none());
+        assert!(session.history.is_empty());
+        assert_eq!(session.daily_challenges.len(), 1);
+
+        // Confirming normal query ends daily challenge
+        session
+            .confirm_query_with_library(&library, &construct_query_request())
+            .unwrap();
+        session.start_game().unwrap();
+        play_until_finished(&mut session);
+
+        assert_eq!(session.history.len(), 1);
+        assert_eq!(session.daily_challenges.len(), 1);
+    }
+
+    #[test]
+    fn weak_keys_raise_weakness_scores() {
+        let mut library = Library::new();
+        library
+            .import_user_dictionary("words", DictionaryType::Word, "か:か\nい:い", skip_saving)
+            .unwrap();
+        let query_request: QueryRequestFromUI = serde_json::from_str(
+            r#"{"dictionaryType":"word","usedDictionaries":[["user_defined","words"]],"keyStrokeCountThreshold":30,"order":"weakness"}"#,
+        )
+        .unwrap();
+        let mut session = TypingSession::new();
+
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        session.start_game().unwrap();
+        play_until_finished_missing(&mut session, 'k');
+
+        let ideal_key_strokes = library
+            .construct_ideal_key_strokes_for_request(&query_request)
+            .unwrap();
+        assert_eq!(ideal_key_strokes, vec!["ka", "i"]);
+        let scores =
+            construct_weakness_scores(&ideal_key_strokes, &session.aggregated_key_stroke_skills());
+        assert!(scores[0] > scores[1]);
+
+        // Without statistics, every vocabulary is equally likely
+        let scores = construct_weakness_scores(&ideal_key_strokes, &[]);
+        assert_eq!(scores[0], scores[1]);
+    }
+
+    #[test]
+    fn missed_vocabularies_are_retried() {
+        let library = construct_word_library();
+        let query_request = construct_word_query_request();
+        let view = |session: &TypingSession| {
+            session
+                .typing_engine
+                .construct_display_info(session.lap_policy.lap_request())
+                .unwrap()
+                .view_info()
+                .view()
+                .to_string()
+        };
+        let mut session = TypingSession::new();
+
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        session.start_game().unwrap();
+        assert_eq!(view(&session), "か い け");
+        play_until_finished_missing(&mut session, 'k');
+        assert_eq!(session.get_result().unwrap().missed_vocabulary_count(), 2);
+
+        session.confirm_missed_vocabularies().unwrap();
+        session.start_game().unwrap();
+        assert_eq!(view(&session), "か け");
+
+        // Nothing to retry after typing without any miss
+        play_until_finished(&mut session);
+        assert_eq!(session.get_result().unwrap().missed_vocabulary_count(), 0);
+        assert!(matches!(
+            session.confirm_missed_vocabularies(),
+            Err(ref e) if matches!(e.kind(), WasmErrorKind::NoMissedVocabulary)
+        ));
+    }
+
+    #[test]
+    fn laps_follow_lap_policy() {
+        let library = construct_word_library();
+        let query_request = construct_word_query_request();
+        let play = |lap_policy: LapPolicy| {
+            let mut session = TypingSession::new();
+            session.set_lap_policy(lap_policy).unwrap();
+            session
+                .confirm_query_with_library(&library, &query_request)
+                .unwrap();
+            let display_information = session.start_game().unwrap();
+            let unfinished_lap_end_positions = display_information.lap_end_positions().to_vec();
+            play_until_finished(&mut session);
+
+            let display_info = session
+                .typing_engine
+                .construct_display_info(session.lap_policy.lap_request())
+                .unwrap();
+            let display_information = session.construct_display_information(display_info);
+
+            (
+                unfinished_lap_end_positions,
+                display_information.lap_end_positions().to_vec(),
+                display_information.lap_end_time().len(),
+            )
+        };
+
+        // Key strokes are "ka i ke"
+        let count = NonZeroUsize::new(3).unwrap();
+        assert_eq!(
+            play(LapPolicy::IdealKeyStroke { count }),
+            (vec![2, 5], vec![2, 5], 2)
+        );
+        assert_eq!(
+            play(LapPolicy::Vocabulary),
+            (vec![1, 3, 6], vec![1, 3, 6], 3)
+        );
+    }
+
+    #[test]
+    fn result_contains_lap_statistics() {
+        let library = construct_word_library();
+        let query_request = construct_word_query_request();
+        let mut session = TypingSession::new();
+        let lap_policy = LapPolicy::IdealKeyStroke {
+            count: NonZeroUsize::new(3).unwrap(),
+        };
+        session.set_lap_policy(lap_policy).unwrap();
+
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        session.start_game().unwrap();
+
+        // Lap policy cannot be changed in the middle of the game
+        assert!(matches!(
+            session.set_lap_policy(LapPolicy::Vocabulary),
+            Err(ref e) if matches!(e.kind(), WasmErrorKind::IllegalGameTransition(..))
+        ));
+        assert_eq!(session.get_lap_policy(), lap_policy);
+        play_until_finished_missing(&mut session, 'k');
+
+        // Key strokes are "ka i ke", and the last "e" is typed after the last lap end
+        let laps = session.this_laps.clone();
+        let counts: Vec<(usize, usize)> = laps
+            .laps()
+            .iter()
+            .map(|lap| (lap.key_stroke_count(), lap.missed_count()))
+            .collect();
+        assert_eq!(counts, vec![(3, 1), (3, 1), (1, 0)]);
+        assert!(!laps.laps()[2].is_complete());
+        // The incomplete last lap is not compared with the others
+        assert_eq!(laps.fastest_lap_index(), Some(1));
+        assert_eq!(laps.slowest_lap_index(), Some(0));
+    }
+
+    #[test]
+    fn display_information_contains_live_metrics() {
+        let library = construct_word_library();
+        let query_request = construct_word_query_request();
+        let mut session = TypingSession::new();
+        session
+            .confirm_query_with_library(&library, &query_request)
+            .unwrap();
+        session.start_game().unwrap();
+
+        let mut stroke = |key: &str, elapsed_time_ms: u64| {
+            let result = session
+                .stroke_key(KeyStrokeInfo {
+                    key: key.to_string(),
+                    elapsed_time_ms,
+                })
+                .unwrap();
+
+            serde_json::to_value(result.display_information).unwrap()["keyStroke"]["liveMetrics"]
+                .clone()
+        };
+
+        stroke("k", 100);
+        let live_metrics = stroke("a", 200);
+        assert_eq!(
+            live_metrics,
+            serde_json::json!({
+                "elapsedTimeMs": 200,
+                "kpm": 600.0,
+                "rollingKpm": 600.0,
+                "accuracy": 1.0,
+                "missedCount": 0,
+                "estimatedRemainingTimeMs": 500,
+            })
+        );
+
+        // Wrong key stroke slows down only the whole speed because it finishes no key stroke
+        let live_metrics = stroke("x", 300);
+        assert_eq!(live_metrics["kpm"], 400.0);
+        assert_eq!(live_metrics["rollingKpm"], 600.0);
+        assert_eq!(live_metrics["missedCount"], 1);
+        assert_eq!(live_metrics["estimatedRemainingTimeMs"], 750);
+    }
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroUsize;
+
+use crate::library::dictionary::DictionaryOrigin;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+/// Represents the last completed sentence of a sentence dictionary.
+/// Sentence is numbered from 1 in the same way as `SentenceRange`.
+pub(crate) struct Bookmark {
+    dictionary_origin: DictionaryOrigin,
+    dictionary_name: String,
+    last_completed_sentence: NonZeroUsize,
+}
+
+impl Bookmark {
+    pub(crate) fn new(
+        dictionary_origin: DictionaryOrigin,
+        dictionary_name: &str,
+        last_completed_sentence: NonZeroUsize,
+    ) -> Self {
+        Self {
+            dictionary_origin,
+            dictionary_name: dictionary_name.to_string(),
+            last_completed_sentence,
+        }
+    }
+
+    pub(crate) fn last_completed_sentence(&self) -> NonZeroUsize {
+        self.last_completed_sentence
+    }
+
+    pub(crate) fn is_for(
+        &self,
+        dictionary_origin: DictionaryOrigin,
+        dictionary_name: &str,
+    ) -> bool {
+        self.dictionary_origin == dictionary_origin && self.dictionary_name == dictionary_name
+    }
+}
+
+/// Finds bookmark of the dictionary
+pub(crate) fn find_bookmark<'a>(
+    bookmarks: &'a [Bookmark],
+    dictionary_origin: DictionaryOrigin,
+    dictionary_name: &str,
+) -> Option<&'a Bookmark> {
+    bookmarks
+        .iter()
+        .find(|bookmark| bookmark.is_for(dictionary_origin, dictionary_name))
+}
+
+/// Replaces bookmark of the same dictionary, or adds it when the dictionary has no bookmark yet
+pub(crate) fn update_bookmark(bookmarks: &mut Vec<Bookmark>, bookmark: Bookmark) {
+    bookmarks.retain(|b| !b.is_for(bookmark.dictionary_origin, &bookmark.dictionary_name));
+    bookmarks.push(bookmark);
+}
+
+/// Moves bookmark of the dictionary to its new name
+pub(crate) fn rename_bookmark(
+    bookmarks: &mut [Bookmark],
+    dictionary_origin: DictionaryOrigin,
+    dictionary_name: &str,
+    new_dictionary_name: &str,
+) {
+    bookmarks
+        .iter_mut()
+        .filter(|bookmark| bookmark.is_for(dictionary_origin, dictionary_name))
+        .for_each(|bookmark| bookmark.dictionary_name = new_dictionary_name.to_string());
+}
+
+/// Removes bookmark of the dictionary
+pub(crate) fn remove_bookmark(
+    bookmarks: &mut Vec<Bookmark>,
+    dictionary_origin: DictionaryOrigin,
+    dictionary_name: &str,
+) {
+    bookmarks.retain(|bookmark| !bookmark.is_for(dictionary_origin, dictionary_name));
+}
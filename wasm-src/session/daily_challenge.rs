@@ -0,0 +1,98 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroUsize;
+use tsify::Tsify;
+
+use crate::error::{WasmError, WasmErrorKind};
+use crate::library::dictionary::{DictionaryOrigin, DictionaryType};
+use crate::library::QueryRequestFromUI;
+use crate::result::{TypingResult, TypingResultRecord};
+
+/// Dictionary used for daily challenge, which must be a builtin word dictionary
+pub(crate) const DAILY_CHALLENGE_DICTIONARY_NAME: &str = "常用漢字";
+
+/// Length of daily challenge in key strokes
+const DAILY_CHALLENGE_KEY_STROKE_COUNT: usize = 300;
+
+/// Parses date of daily challenge in `YYYY-MM-DD` format
+pub(crate) fn parse_daily_challenge_date(date: &str) -> Result<NaiveDate, WasmError> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| WasmError::new(WasmErrorKind::InvalidDate(date.to_string())))
+}
+
+/// Constructs query request of daily challenge for the date.
+/// Seed is derived from the date, so everyone gets the same query on the same day.
+pub(crate) fn construct_daily_challenge_request(date: NaiveDate) -> QueryRequestFromUI {
+    let seed = u32::try_from(date.num_days_from_ce()).unwrap_or_default();
+
+    QueryRequestFromUI::new(
+        DictionaryType::Word,
+        vec![(
+            DictionaryOrigin::Builtin,
+            DAILY_CHALLENGE_DICTIONARY_NAME.to_string(),
+        )],
+        NonZeroUsize::new(DAILY_CHALLENGE_KEY_STROKE_COUNT),
+    )
+    .with_seed(seed)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+/// Represents a finished daily challenge.
+/// This is kept separately from history so that normal statistics are not affected.
+pub(crate) struct DailyChallengeRecord {
+    date: NaiveDate,
+    finished_at: DateTime<Utc>,
+    result: TypingResultRecord,
+}
+
+impl DailyChallengeRecord {
+    pub(crate) fn new(date: NaiveDate, result: TypingResultRecord) -> Self {
+        Self {
+            date,
+            finished_at: Utc::now(),
+            result,
+        }
+    }
+
+    fn construct_daily_challenge_entry(&self) -> DailyChallengeEntry {
+        DailyChallengeEntry {
+            date: self.date.format("%Y-%m-%d").to_string(),
+            finished_at: self.finished_at.to_rfc3339(),
+            result: self.result.clone().into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi)]
+/// A struct representing a finished daily challenge
+pub struct DailyChallengeEntry {
+    /// Date of the challenge in `YYYY-MM-DD` format
+    date: String,
+    /// Timestamp in RFC 3339 format
+    finished_at: String,
+    result: TypingResult,
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi)]
+/// A struct representing finished daily challenges ordered from the newest
+pub struct DailyChallengeHistory {
+    entries: Vec<DailyChallengeEntry>,
+}
+
+/// Constructs daily challenge history from records ordered from the oldest
+pub(crate) fn construct_daily_challenge_history(
+    records: &[DailyChallengeRecord],
+) -> DailyChallengeHistory {
+    DailyChallengeHistory {
+        entries: records
+            .iter()
+            .rev()
+            .map(DailyChallengeRecord::construct_daily_challenge_entry)
+            .collect(),
+    }
+}
@@ -0,0 +1,302 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroUsize;
+use tsify::Tsify;
+
+use crate::error::{WasmError, WasmErrorKind};
+use crate::library::dictionary::{DictionaryOrigin, DictionaryType};
+use crate::library::QueryRequestFromUI;
+use crate::result::{TypingResult, TypingResultRecord};
+use crate::utils::construct_csv;
+
+/// Maximum count of games kept in history.
+/// Each record has whole result including skill of each key, so history is limited not to fill
+/// browser storage.
+pub(crate) const MAX_HISTORY_LENGTH: usize = 200;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+/// Represents a finished game kept in history.
+/// Whole result is kept so that it can be aggregated again.
+pub(crate) struct HistoryRecord {
+    id: String,
+    started_at: DateTime<Utc>,
+    finished_at: DateTime<Utc>,
+    dictionary_type: DictionaryType,
+    used_dictionaries: Vec<(DictionaryOrigin, String)>,
+    key_stroke_count_threshold: Option<NonZeroUsize>,
+    /// Seed of random order, which is missing in records of games played in order
+    #[serde(default)]
+    seed: Option<u32>,
+    result: TypingResultRecord,
+}
+
+impl HistoryRecord {
+    pub(crate) fn new(
+        query_request: &QueryRequestFromUI,
+        started_at: DateTime<Utc>,
+        finished_at: DateTime<Utc>,
+        result: TypingResultRecord,
+    ) -> Result<Self, WasmError> {
+        Ok(Self {
+            id: generate_id()?,
+            started_at,
+            finished_at,
+            dictionary_type: query_request.dictionary_type(),
+            used_dictionaries: query_request.used_dictionaries().to_vec(),
+            key_stroke_count_threshold: query_request.key_stroke_count_threshold(),
+            seed: query_request.seed(),
+            result,
+        })
+    }
+
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub(crate) fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    pub(crate) fn result(&self) -> &TypingResultRecord {
+        &self.result
+    }
+
+    /// Construct summarized entry to be passed to UI
+    pub(crate) fn construct_history_entry(&self) -> HistoryEntry {
+        HistoryEntry {
+            id: self.id.clone(),
+            started_at: self.started_at.to_rfc3339(),
+            finished_at: self.finished_at.to_rfc3339(),
+            dictionary_type: self.dictionary_type,
+            used_dictionaries: self.used_dictionaries.clone(),
+            key_stroke_count_threshold: self.key_stroke_count_threshold.map(NonZeroUsize::get),
+            seed: self.seed,
+            result: self.result.clone().into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi)]
+/// A struct representing a finished game in history
+pub struct HistoryEntry {
+    id: String,
+    /// Timestamp in RFC 3339 format
+    started_at: String,
+    /// Timestamp in RFC 3339 format
+    finished_at: String,
+    dictionary_type: DictionaryType,
+    used_dictionaries: Vec<(DictionaryOrigin, String)>,
+    key_stroke_count_threshold: Option<usize>,
+    seed: Option<u32>,
+    result: TypingResult,
+}
+
+#[cfg(test)]
+impl HistoryEntry {
+    pub(crate) fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi)]
+/// A struct representing a page of history, which is ordered from the newest game
+pub struct HistoryPage {
+    entries: Vec<HistoryEntry>,
+    /// Count of all entries in history, not only in this page
+    total_count: usize,
+}
+
+#[cfg(test)]
+impl HistoryPage {
+    pub(crate) fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    pub(crate) fn total_count(&self) -> usize {
+        self.total_count
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+#[tsify(into_wasm_abi)]
+/// A struct representing how many games are imported from statistics file
+pub struct StatisticsImportSummary {
+    imported_count: usize,
+    /// Count of games skipped because they are already in history
+    duplicated_count: usize,
+}
+
+impl StatisticsImportSummary {
+    pub(crate) fn new(imported_count: usize, duplicated_count: usize) -> Self {
+        Self {
+            imported_count,
+            duplicated_count,
+        }
+    }
+}
+
+#[cfg(test)]
+impl StatisticsImportSummary {
+    pub(crate) fn imported_count(&self) -> usize {
+        self.imported_count
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+/// Represents games removed from history whose results are still counted in aggregated result,
+/// so that they are not counted again by importing.
+///
+/// Old games are covered by a single start time instead of each identifier, so this is bounded
+/// regardless of how many games are removed.
+pub(crate) struct RemovedHistory {
+    /// Every game started at or before this is regarded as removed
+    started_until: Option<DateTime<Utc>>,
+    /// Identifiers and start times of games started after `started_until` and deleted from
+    /// history, ordered from the oldest
+    entries: Vec<(String, DateTime<Utc>)>,
+}
+
+impl RemovedHistory {
+    /// Remembers records deleted from history individually.
+    /// Beyond `MAX_HISTORY_LENGTH` entries, the oldest ones are covered by the start time instead.
+    pub(crate) fn remember(&mut self, records: &[HistoryRecord]) {
+        let entries: Vec<(String, DateTime<Utc>)> = records
+            .iter()
+            .filter(|record| !self.covers(record.started_at))
+            .map(|record| (record.id.clone(), record.started_at))
+            .collect();
+        self.entries.extend(entries);
+        self.entries.sort_by_key(|(_, started_at)| *started_at);
+
+        let overflow = self.entries.len().saturating_sub(MAX_HISTORY_LENGTH);
+        if overflow != 0 {
+            self.cover_until(self.entries[overflow - 1].1);
+        }
+    }
+
+    /// Remembers the oldest records dropped from history ordered from the oldest.
+    /// Such records are older than any record left in history, so only the start time of the newest
+    /// one is kept.
+    pub(crate) fn remember_dropped(&mut self, dropped_records: &[HistoryRecord]) {
+        if let Some(record) = dropped_records.last() {
+            self.cover_until(record.started_at);
+        }
+    }
+
+    pub(crate) fn contains(&self, record: &HistoryRecord) -> bool {
+        self.covers(record.started_at) || self.entries.iter().any(|(id, _)| *id == record.id)
+    }
+
+    fn covers(&self, started_at: DateTime<Utc>) -> bool {
+        self.started_until
+            .is_some_and(|started_until| started_at <= started_until)
+    }
+
+    fn cover_until(&mut self, started_at: DateTime<Utc>) {
+        let started_until = self
+            .started_until
+            .map_or(started_at, |started_until| started_until.max(started_at));
+
+        self.started_until.replace(started_until);
+        self.entries
+            .retain(|(_, started_at)| *started_at > started_until);
+    }
+}
+
+#[cfg(test)]
+impl RemovedHistory {
+    pub(crate) fn entry_count(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// Drops the oldest records beyond `MAX_HISTORY_LENGTH` from records ordered from the oldest.
+/// Results of dropped records are still counted in aggregated result.
+/// Returns the dropped records.
+pub(crate) fn truncate_history(records: &mut Vec<HistoryRecord>) -> Vec<HistoryRecord> {
+    let overflow = records.len().saturating_sub(MAX_HISTORY_LENGTH);
+
+    records.drain(..overflow).collect()
+}
+
+/// Constructs a page of `limit` entries from `offset`-th newest record
+pub(crate) fn construct_history_page(
+    records: &[HistoryRecord],
+    offset: usize,
+    limit: usize,
+) -> HistoryPage {
+    HistoryPage {
+        entries: records
+            .iter()
+            .rev()
+            .skip(offset)
+            .take(limit)
+            .map(HistoryRecord::construct_history_entry)
+            .collect(),
+        total_count: records.len(),
+    }
+}
+
+/// Constructs CSV of history with one row for each game from the oldest
+pub(crate) fn construct_history_csv(records: &[HistoryRecord]) -> String {
+    let mut header = vec![
+        "id",
+        "started_at",
+        "finished_at",
+        "dictionary_type",
+        "used_dictionaries",
+        "key_stroke_count_threshold",
+        "seed",
+    ];
+    header.extend(TypingResult::CSV_HEADER);
+
+    let rows: Vec<Vec<String>> = records
+        .iter()
+        .map(|record| {
+            let entry = record.construct_history_entry();
+            let dictionary_type = match entry.dictionary_type {
+                DictionaryType::Word => "word",
+                DictionaryType::Sentence => "sentence",
+            };
+
+            let mut fields = vec![
+                entry.id,
+                entry.started_at,
+                entry.finished_at,
+                dictionary_type.to_string(),
+                entry
+                    .used_dictionaries
+                    .iter()
+                    .map(|(_, name)| name.as_str())
+                    .collect::<Vec<&str>>()
+                    .join(";"),
+                entry
+                    .key_stroke_count_threshold
+                    .map(|threshold| threshold.to_string())
+                    .unwrap_or_default(),
+                entry.seed.map(|seed| seed.to_string()).unwrap_or_default(),
+            ];
+            fields.extend(entry.result.csv_fields());
+
+            fields
+        })
+        .collect();
+
+    construct_csv(&header, &rows)
+}
+
+/// Generates random identifier of history record
+fn generate_id() -> Result<String, WasmError> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| WasmError::new(WasmErrorKind::InternalError(e.to_string())))?;
+
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+use typing_engine::EntitySummaryStatistics;
+
+use crate::result::KeyStrokeSkillRecord;
+
+#[derive(Debug, Clone, Default)]
+/// Skill of each key observed from key strokes of current game.
+///
+/// Typing engine constructs skill statistics only for finished games, so this is used for the
+/// result of games which are not finished such as aborted or time limited ones.
+/// Key stroke is regarded as correct when it advances the typed part, in the same way as typing
+/// engine, and the required time is measured from the previous correct key stroke.
+pub(crate) struct KeyStrokeSkills {
+    finished_count: usize,
+    wrong_count: usize,
+    last_correct_time: Duration,
+    /// Wrong key strokes typed after the last correct key stroke
+    pending_wrong_key_strokes: Vec<String>,
+    skills: BTreeMap<String, KeyStrokeSkillRecord>,
+}
+
+impl KeyStrokeSkills {
+    /// Records the key stroke by comparing key stroke summary with the one before it
+    pub(crate) fn record(
+        &mut self,
+        key_stroke: char,
+        summary_statistics: &EntitySummaryStatistics,
+        elapsed_time: Duration,
+    ) {
+        let key_stroke = key_stroke.to_string();
+
+        if summary_statistics.finished_count() > self.finished_count {
+            let skill = KeyStrokeSkillRecord::new(
+                &key_stroke,
+                elapsed_time.saturating_sub(self.last_correct_time),
+                &self.pending_wrong_key_strokes,
+            );
+            let skill = match self.skills.remove(&key_stroke) {
+                Some(existing_skill) => existing_skill + skill,
+                None => skill,
+            };
+
+            self.skills.insert(key_stroke, skill);
+            self.last_correct_time = elapsed_time;
+            self.pending_wrong_key_strokes.clear();
+        } else if summary_statistics.wrong_count() > self.wrong_count {
+            self.pending_wrong_key_strokes.push(key_stroke);
+        }
+
+        self.finished_count = summary_statistics.finished_count();
+        self.wrong_count = summary_statistics.wrong_count();
+    }
+
+    /// Returns skills of keys ordered by the key
+    pub(crate) fn construct_skills(&self) -> Vec<KeyStrokeSkillRecord> {
+        self.skills.values().cloned().collect()
+    }
+}
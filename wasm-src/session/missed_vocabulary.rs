@@ -0,0 +1,84 @@
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
+use typing_engine::{VocabularyEntry, VocabularyOrder};
+
+use crate::library::QuerySeparator;
+
+/// A function choosing index of the next vocabulary in the same way as `VocabularyOrder::Arbitrary`
+pub(crate) type VocabularyChooser = Box<dyn Fn(&Option<usize>, &[&VocabularyEntry]) -> usize>;
+
+/// Returns order which also records indices of chosen vocabularies
+pub(crate) fn recording_order(
+    chooser: VocabularyChooser,
+    chosen_indices: Rc<RefCell<Vec<usize>>>,
+) -> VocabularyOrder {
+    VocabularyOrder::Arbitrary(Box::new(move |prev_index, vocabulary_entries| {
+        let index = chooser(prev_index, vocabulary_entries);
+        chosen_indices.borrow_mut().push(index);
+
+        index
+    }))
+}
+
+#[derive(Debug, Clone, Default)]
+/// Vocabularies in query of a game with their positions in the view of the query
+pub(crate) struct QueryVocabularies {
+    vocabularies: Vec<(Range<usize>, VocabularyEntry)>,
+}
+
+impl QueryVocabularies {
+    /// Lays out vocabularies in the order they are chosen for query.
+    /// Vocabularies chosen beyond the end of query are kept but never contain wrong positions.
+    pub(crate) fn new(vocabulary_entries: Vec<VocabularyEntry>, separator: QuerySeparator) -> Self {
+        let separator_length = match separator {
+            QuerySeparator::None => 0,
+            QuerySeparator::WhiteSpace => 1,
+        };
+
+        let mut start = 0;
+        let vocabularies = vocabulary_entries
+            .into_iter()
+            .map(|vocabulary_entry| {
+                let end = start + vocabulary_entry.view().chars().count();
+                let range = start..end;
+                start = end + separator_length;
+
+                (range, vocabulary_entry)
+            })
+            .collect();
+
+        Self { vocabularies }
+    }
+
+    /// Returns whether the position in the view is the last position of a vocabulary
+    pub(crate) fn is_vocabulary_end(&self, view_position: usize) -> bool {
+        self.vocabularies
+            .iter()
+            .any(|(range, _)| range.end == view_position + 1)
+    }
+
+    /// Returns vocabularies which contain any of the wrong positions of the view.
+    /// Vocabulary chosen multiple times is returned only once.
+    pub(crate) fn construct_missed_vocabularies(
+        &self,
+        wrong_positions: &[usize],
+    ) -> Vec<VocabularyEntry> {
+        let mut missed_vocabularies: Vec<VocabularyEntry> = vec![];
+
+        self.vocabularies
+            .iter()
+            .filter(|(range, _)| {
+                wrong_positions
+                    .iter()
+                    .any(|position| range.contains(position))
+            })
+            .for_each(|(_, vocabulary_entry)| {
+                if !missed_vocabularies.contains(vocabulary_entry) {
+                    missed_vocabularies.push(vocabulary_entry.clone());
+                }
+            });
+
+        missed_vocabularies
+    }
+}
@@ -0,0 +1,51 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Count of key strokes which rolling KPM is calculated over
+const ROLLING_KEY_STROKE_COUNT: usize = 20;
+
+#[derive(Debug, Clone)]
+/// Times when the recent key strokes are finished, which are used for speed of the last few
+/// key strokes rather than the whole game
+pub(crate) struct RecentKeyStrokes {
+    finished_count: usize,
+    /// Finished times of the last key strokes with the time before them, ordered from the oldest
+    finished_times: VecDeque<Duration>,
+}
+
+impl RecentKeyStrokes {
+    /// Constructs for a game started just now
+    pub(crate) fn new() -> Self {
+        Self {
+            finished_count: 0,
+            finished_times: VecDeque::from([Duration::ZERO]),
+        }
+    }
+
+    /// Records key strokes finished since the last record
+    pub(crate) fn record(&mut self, finished_count: usize, elapsed_time: Duration) {
+        for _ in self.finished_count..finished_count {
+            self.finished_times.push_back(elapsed_time);
+        }
+        self.finished_count = finished_count;
+
+        while self.finished_times.len() > ROLLING_KEY_STROKE_COUNT + 1 {
+            self.finished_times.pop_front();
+        }
+    }
+
+    /// Returns key strokes per minute over the recent key strokes
+    pub(crate) fn rolling_kpm(&self) -> f64 {
+        let key_stroke_count = self.finished_times.len() - 1;
+        let duration = match (self.finished_times.front(), self.finished_times.back()) {
+            (Some(front), Some(back)) => *back - *front,
+            _ => Duration::ZERO,
+        };
+
+        if duration.is_zero() {
+            0.0
+        } else {
+            key_stroke_count as f64 * 60000.0 / duration.as_millis() as f64
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use typing_engine::TypingResult as LibTypingResult;
+
+use super::bookmark::Bookmark;
+use super::daily_challenge::DailyChallengeRecord;
+use super::history::{HistoryRecord, RemovedHistory};
+use crate::error::{WasmError, WasmErrorKind};
+use crate::result::TypingResultRecord;
+use crate::utils::local_storage;
+
+/// Key of the browser storage entry holding statistics
+const STATISTICS_STORAGE_KEY: &str = "typer-concierge-web.statistics";
+
+/// Version of the persisted statistics schema.
+/// This must be incremented when the persisted form is changed incompatibly.
+pub(crate) const STATISTICS_SCHEMA_VERSION: u32 = 2;
+
+/// Version whose results are in the serialized form of typing engine itself
+const ENGINE_RESULT_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Deserialize)]
+/// Only the version part of persisted statistics, which is checked before decoding the whole
+struct SchemaVersion {
+    version: u32,
+}
+
+/// Parts of persisted statistics in the same order as its fields
+pub(crate) type StatisticsParts = (
+    Option<TypingResultRecord>,
+    Vec<HistoryRecord>,
+    Vec<Bookmark>,
+    Vec<DailyChallengeRecord>,
+    RemovedHistory,
+);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+/// Represents statistics persisted in browser storage.
+/// Aggregated result contains counts, per-key skill statistics and total time.
+/// Results are persisted in the form owned by this crate, so that the schema is kept stable
+/// regardless of the version of typing engine.
+pub(crate) struct PersistedStatistics {
+    version: u32,
+    aggregated_result: Option<TypingResultRecord>,
+    /// History is added without changing version, so it can be missing in persisted statistics
+    #[serde(default)]
+    history: Vec<HistoryRecord>,
+    /// Bookmarks of sentence dictionaries, which can be missing in the same way as history
+    #[serde(default)]
+    bookmarks: Vec<Bookmark>,
+    /// Daily challenges, which can be missing in the same way as history
+    #[serde(default)]
+    daily_challenges: Vec<DailyChallengeRecord>,
+    /// Games removed from history but still counted in aggregated result, which can be missing in
+    /// the same way as history
+    #[serde(default)]
+    removed_history: RemovedHistory,
+}
+
+impl PersistedStatistics {
+    pub(crate) fn new(
+        aggregated_result: Option<TypingResultRecord>,
+        history: Vec<HistoryRecord>,
+        bookmarks: Vec<Bookmark>,
+        daily_challenges: Vec<DailyChallengeRecord>,
+        removed_history: RemovedHistory,
+    ) -> Self {
+        Self {
+            version: STATISTICS_SCHEMA_VERSION,
+            aggregated_result,
+            history,
+            bookmarks,
+            daily_challenges,
+            removed_history,
+        }
+    }
+
+    pub(crate) fn into_parts(self) -> StatisticsParts {
+        (
+            self.aggregated_result,
+            self.history,
+            self.bookmarks,
+            self.daily_challenges,
+            self.removed_history,
+        )
+    }
+
+    /// Decodes persisted statistics from JSON after checking its schema version.
+    /// Statistics of older versions are migrated to the current version.
+    pub(crate) fn from_json(json: &str) -> Result<Self, WasmError> {
+        let schema_version: SchemaVersion = serde_json::from_str(json)?;
+        match schema_version.version {
+            STATISTICS_SCHEMA_VERSION => Ok(serde_json::from_str(json)?),
+            ENGINE_RESULT_SCHEMA_VERSION => Ok(serde_json::from_value(migrate_engine_results(
+                serde_json::from_str(json)?,
+            )?)?),
+            version => Err(WasmError::new(WasmErrorKind::UnsupportedStatisticsVersion(
+                version,
+            ))),
+        }
+    }
+
+    pub(crate) fn to_json(&self) -> Result<String, WasmError> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+/// Converts results in the serialized form of typing engine into the form owned by this crate
+fn migrate_engine_results(
+    mut statistics: serde_json::Value,
+) -> Result<serde_json::Value, WasmError> {
+    if let Some(aggregated_result) = statistics.get_mut("aggregatedResult") {
+        migrate_engine_result(aggregated_result)?;
+    }
+    for key in ["history", "dailyChallenges"] {
+        if let Some(serde_json::Value::Array(records)) = statistics.get_mut(key) {
+            for record in records.iter_mut() {
+                if let Some(result) = record.get_mut("result") {
+                    migrate_engine_result(result)?;
+                }
+            }
+        }
+    }
+    statistics["version"] = STATISTICS_SCHEMA_VERSION.into();
+
+    Ok(statistics)
+}
+
+fn migrate_engine_result(result: &mut serde_json::Value) -> Result<(), WasmError> {
+    if result.is_null() {
+        return Ok(());
+    }
+
+    let engine_result: LibTypingResult = serde_json::from_value(result.take())?;
+    *result = serde_json::to_value(TypingResultRecord::from(engine_result))?;
+
+    Ok(())
+}
+
+/// Loads statistics from browser storage.
+/// When nothing is persisted yet, `None` is returned.
+pub(crate) fn load_statistics(
+    window: &web_sys::Window,
+) -> Result<Option<PersistedStatistics>, WasmError> {
+    match local_storage(window)?.get_item(STATISTICS_STORAGE_KEY)? {
+        Some(json) => Ok(Some(PersistedStatistics::from_json(&json)?)),
+        None => Ok(None),
+    }
+}
+
+/// Saves statistics to browser storage
+pub(crate) fn save_statistics(
+    window: &web_sys::Window,
+    statistics: &PersistedStatistics,
+) -> Result<(), WasmError> {
+    local_storage(window)?.set_item(STATISTICS_STORAGE_KEY, &statistics.to_json()?)?;
+
+    Ok(())
+}
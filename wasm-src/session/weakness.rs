@@ -0,0 +1,94 @@
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use super::missed_vocabulary::VocabularyChooser;
+use crate::result::SingleKeyStrokeSkill;
+
+/// Weakness of keys which are not typed yet, which is the same as a key of average skill
+const UNKNOWN_KEY_WEAKNESS: f64 = 1.0;
+
+/// Scores each vocabulary by how weak the user is at the keys of its ideal key strokes.
+/// Score of a vocabulary is the mean weakness of its key strokes, so long vocabularies are not
+/// preferred only because of their length.
+pub(crate) fn construct_weakness_scores(
+    ideal_key_strokes: &[&str],
+    skills: &[SingleKeyStrokeSkill],
+) -> Vec<f64> {
+    let weaknesses = construct_key_stroke_weaknesses(skills);
+
+    ideal_key_strokes
+        .iter()
+        .map(|key_strokes| {
+            let key_stroke_count = key_strokes.chars().count();
+            if key_stroke_count == 0 {
+                return UNKNOWN_KEY_WEAKNESS;
+            }
+
+            let total_weakness: f64 = key_strokes
+                .chars()
+                .map(|key_stroke| {
+                    weaknesses
+                        .get(&key_stroke.to_string())
+                        .copied()
+                        .unwrap_or(UNKNOWN_KEY_WEAKNESS)
+                })
+                .sum();
+
+            total_weakness / key_stroke_count as f64
+        })
+        .collect()
+}
+
+/// Returns chooser choosing vocabularies randomly in proportion to the scores,
+/// which is reproducible with the same seed and scores
+pub(crate) fn seeded_weighted_chooser(seed: u32, scores: Vec<f64>) -> VocabularyChooser {
+    let rng = RefCell::new(ChaCha8Rng::seed_from_u64(seed.into()));
+    // Scores are never negative, but fall back to uniform distribution just in case
+    let distribution = WeightedIndex::new(&scores)
+        .or_else(|_| WeightedIndex::new(vec![1.0; scores.len()]))
+        .unwrap();
+
+    Box::new(move |_, _| distribution.sample(&mut *rng.borrow_mut()))
+}
+
+/// Calculates weakness of each key relative to the average of all keys.
+/// Weakness is the mean of relative miss rate and relative average time, so average key is 1.0
+/// and a key missed or typed twice as often or slow as average gets higher weakness.
+fn construct_key_stroke_weaknesses(skills: &[SingleKeyStrokeSkill]) -> HashMap<String, f64> {
+    let skills: Vec<&SingleKeyStrokeSkill> =
+        skills.iter().filter(|skill| skill.count() != 0).collect();
+    if skills.is_empty() {
+        return HashMap::new();
+    }
+
+    let mean = |f: fn(&SingleKeyStrokeSkill) -> f64| {
+        skills.iter().map(|skill| f(skill)).sum::<f64>() / skills.len() as f64
+    };
+    let miss_rate = |skill: &SingleKeyStrokeSkill| 1.0 - skill.accuracy();
+    let average_time = |skill: &SingleKeyStrokeSkill| skill.average_time_ms() as f64;
+    let mean_miss_rate = mean(miss_rate);
+    let mean_average_time = mean(average_time);
+
+    // Relative value is not meaningful when nobody misses or every key takes no time
+    let relative = |value: f64, mean: f64| {
+        if mean > 0.0 {
+            value / mean
+        } else {
+            UNKNOWN_KEY_WEAKNESS
+        }
+    };
+
+    skills
+        .iter()
+        .map(|skill| {
+            let weakness = (relative(miss_rate(skill), mean_miss_rate)
+                + relative(average_time(skill), mean_average_time))
+                / 2.0;
+
+            (skill.key_stroke().to_string(), weakness)
+        })
+        .collect()
+}
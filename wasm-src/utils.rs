@@ -1,3 +1,5 @@
+use crate::error::{WasmError, WasmErrorKind};
+
 pub fn set_panic_hook() {
     // When the `console_error_panic_hook` feature is enabled, we can call the
     // `set_panic_hook` function at least once during initialization, and then
@@ -8,3 +10,40 @@ pub fn set_panic_hook() {
     #[cfg(feature = "console_error_panic_hook")]
     console_error_panic_hook::set_once();
 }
+
+/// Returns global `window`
+pub(crate) fn get_window() -> Result<web_sys::Window, WasmError> {
+    web_sys::window().ok_or(WasmError::new(WasmErrorKind::CannotGetWindow))
+}
+
+/// Returns local storage of the window
+pub(crate) fn local_storage(window: &web_sys::Window) -> Result<web_sys::Storage, WasmError> {
+    window
+        .local_storage()?
+        .ok_or(WasmError::new(WasmErrorKind::CannotGetStorage))
+}
+
+/// Constructs a CSV document from header and rows.
+/// Fields containing comma, double quote or line break are quoted.
+pub(crate) fn construct_csv(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut csv = String::new();
+
+    csv.push_str(&header.join(","));
+    csv.push('\n');
+
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|field| escape_csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}